@@ -0,0 +1,172 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use crate::build::{BuildPlan, OperationKind};
+use crate::config::Abi;
+use crate::error::KsmmError;
+use crate::i18n::tr;
+
+/// 按架构区分的可执行文件所在的目录，每个架构一个子目录，例如 `bin/arm64-v8a/`
+pub const ABI_DIR: &str = "bin";
+
+/// 安装时可执行文件被复制到的目录
+pub const INSTALL_DIR: &str = "system/bin";
+
+/// 生成的安装代码追加到的脚本
+const INSTALLER_SCRIPT: &str = "customize.sh";
+
+impl Abi {
+    pub const ALL: [Abi; 4] = [Abi::Arm64, Abi::Arm, Abi::X64, Abi::X86];
+
+    /// Android ABI 名称
+    pub fn name(self) -> &'static str {
+        match self {
+            Abi::Arm64 => "arm64-v8a",
+            Abi::Arm => "armeabi-v7a",
+            Abi::X64 => "x86_64",
+            Abi::X86 => "x86",
+        }
+    }
+
+    /// 安装时 `$ARCH` 的值
+    pub fn arch(self) -> &'static str {
+        match self {
+            Abi::Arm64 => "arm64",
+            Abi::Arm => "arm",
+            Abi::X64 => "x64",
+            Abi::X86 => "x86",
+        }
+    }
+
+    /// 按目录名识别架构，ABI 名称与 `$ARCH` 的值都可以使用
+    pub fn from_dir_name(name: &str) -> Option<Abi> {
+        Abi::ALL.into_iter().find(|abi| abi.name() == name || abi.arch() == name)
+    }
+
+    /// Rust 目标三元组对应的架构，例如 `aarch64-linux-android` 为 arm64-v8a
+    pub fn from_target(target: &str) -> Option<Abi> {
+        match target.split('-').next()? {
+            "aarch64" => Some(Abi::Arm64),
+            "armv7" | "armv7a" | "thumbv7neon" | "arm" => Some(Abi::Arm),
+            "x86_64" => Some(Abi::X64),
+            "i686" | "i586" => Some(Abi::X86),
+            _ => None,
+        }
+    }
+}
+
+/// 一个架构在构建目录中的可执行文件
+#[derive(Debug, Clone, Default)]
+pub struct AbiFiles {
+    /// `bin/` 下的目录名，可能是 ABI 名称或 `$ARCH` 的值
+    pub dir: String,
+    /// 目录中直接包含的文件与子目录
+    pub files: BTreeSet<String>,
+}
+
+/// 复制计划中 `bin/<abi>/` 的内容
+///
+/// 打包时这些文件保留在 `bin/` 中，构建时在 customize.sh 末尾生成安装代码，
+/// 安装时只把与设备 `$ARCH` 对应的文件复制到 `system/bin` 并删除所有架构目录。
+#[derive(Debug, Clone, Default)]
+pub struct AbiBinaries {
+    pub abis: BTreeMap<Abi, AbiFiles>,
+}
+
+impl AbiBinaries {
+    /// 从复制计划中找出将被打包到 `bin/<abi>/` 的文件，`bin/` 中不是架构名称的路径不受影响
+    pub fn from_plan(plan: &BuildPlan, build_dir: &Path) -> Result<Self, KsmmError> {
+        let mut abis: BTreeMap<Abi, AbiFiles> = BTreeMap::new();
+        for op in &plan.operations {
+            if matches!(op.kind, OperationKind::Include(_) | OperationKind::Ignore(_)) {
+                continue;
+            }
+            let Ok(rel) = op.dst.strip_prefix(build_dir.join(ABI_DIR)) else {
+                continue;
+            };
+            let components: Vec<String> =
+                rel.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+            let Some(abi) = components.first().and_then(|dir| Abi::from_dir_name(dir)) else {
+                continue;
+            };
+
+            let files = abis.entry(abi).or_insert_with(|| AbiFiles { dir: components[0].clone(), ..AbiFiles::default() });
+            // 同一个架构同时有 bin/arm64/ 与 bin/arm64-v8a/ 时无法确定安装哪一个
+            if files.dir != components[0] {
+                return Err(KsmmError::Build(tr!(abi_duplicate_dir, abi.name(), files.dir, components[0])));
+            }
+            if let Some(file) = components.get(1) {
+                files.files.insert(file.clone());
+            }
+        }
+        Ok(AbiBinaries { abis })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.abis.is_empty()
+    }
+
+    /// 确认 `required` 中的每个架构都有目录，并包含其他架构提供的所有可执行文件
+    pub fn check(&self, required: &[Abi]) -> Result<(), KsmmError> {
+        let all_files: BTreeSet<&String> = self.abis.values().flat_map(|abi| &abi.files).collect();
+        for &abi in required {
+            let files = match self.abis.get(&abi) {
+                Some(files) if !files.files.is_empty() => files,
+                _ => return Err(KsmmError::Build(tr!(abi_missing, abi.name(), ABI_DIR))),
+            };
+            if let Some(file) = all_files.iter().find(|file| !files.files.contains(**file)) {
+                return Err(KsmmError::Build(tr!(abi_missing_binary, abi.name(), file, ABI_DIR, files.dir)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 按 `$ARCH` 安装对应架构的可执行文件的 shell 代码，不支持的架构中止安装
+    ///
+    /// 代码会被打包进 zip，输出固定使用英文，不随构建机的语言变化。
+    pub fn install_script(&self) -> String {
+        let mut script = format!(
+            "\n# Generated by ksmm build: installs the files in {}/<abi>/ for the device architecture into {}\n",
+            ABI_DIR, INSTALL_DIR
+        );
+        script.push_str("case \"$ARCH\" in\n");
+        for (abi, files) in &self.abis {
+            script.push_str(&format!("    {}) KSMM_ABI_DIR=\"{}\" ;;\n", abi.arch(), files.dir));
+        }
+        script.push_str("    *) abort \"! This module does not support the device architecture $ARCH\" ;;\n");
+        script.push_str("esac\n");
+        script.push_str(&format!("ui_print \"- Installing $KSMM_ABI_DIR binaries into {}\"\n", INSTALL_DIR));
+        script.push_str(&format!("mkdir -p \"$MODPATH/{}\"\n", INSTALL_DIR));
+        script.push_str(&format!("cp -af \"$MODPATH/{}/$KSMM_ABI_DIR/.\" \"$MODPATH/{}/\"\n", ABI_DIR, INSTALL_DIR));
+        script.push_str(&format!("for file in \"$MODPATH/{}/$KSMM_ABI_DIR\"/*; do\n", ABI_DIR));
+        script.push_str("    [ -e \"$file\" ] || continue\n");
+        script.push_str(&format!("    set_perm_recursive \"$MODPATH/{}/${{file##*/}}\" 0 0 0755 0755\n", INSTALL_DIR));
+        script.push_str("done\n");
+        // 只删除架构目录，bin/ 中的其他文件保留
+        for files in self.abis.values() {
+            script.push_str(&format!("rm -rf \"$MODPATH/{}/{}\"\n", ABI_DIR, files.dir));
+        }
+        script.push_str(&format!("rmdir \"$MODPATH/{}\" 2>/dev/null\n", ABI_DIR));
+        script.push_str("unset KSMM_ABI_DIR file\n");
+        script
+    }
+
+    /// 把安装代码追加到构建目录中的 customize.sh，项目中没有时新建
+    ///
+    /// 构建目录中的 customize.sh 可能是指向项目文件的符号链接，因此总是替换为新文件而不是原地追加。
+    pub fn write_installer(&self, build_dir: &Path) -> Result<(), KsmmError> {
+        let path = build_dir.join(INSTALLER_SCRIPT);
+        let mut script = match fs::read_to_string(&path) {
+            Ok(existing) => {
+                fs::remove_file(&path).map_err(|e| KsmmError::io(tr!(write_file_failed, path.display(), e), e))?;
+                existing
+            }
+            Err(_) => "#!/system/bin/sh\n".to_string(),
+        };
+        if !script.ends_with('\n') {
+            script.push('\n');
+        }
+        script.push_str(&self.install_script());
+        fs::write(&path, script).map_err(|e| KsmmError::io(tr!(write_file_failed, path.display(), e), e))
+    }
+}
@@ -0,0 +1,908 @@
+use chrono::{Datelike, Timelike};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use zip::write::FileOptions;
+use crate::config::{self, Config, PermissionMap, TemplateError};
+use crate::error::KsmmError;
+use crate::i18n::tr;
+use crate::ignore::{IgnoreRule, IgnoreRules};
+use crate::module_prop::ModuleProp;
+use crate::native::NativeBinary;
+use crate::project::Project;
+use crate::variant::Variant;
+
+/// 构建配置文件，相对于项目根目录
+pub const BUILD_CONF: &str = ".ksmm/build.conf";
+
+/// `build.artifact_name` 中可用的占位符
+const ARTIFACT_PLACEHOLDERS: &[&str] = &["id", "version", "versionCode", "commit", "date", "variant", "signed"];
+
+/// 签名后的 zip 中 `{signed}` 的值，未签名时为空
+const SIGNED_SUFFIX: &str = "_signed";
+
+/// ksmm 创建构建目录与发布目录时写入的标记文件，不会被打包
+///
+/// 清空目录前必须存在这个文件（或目录为空），防止配置错误时删除不是由 ksmm 创建的目录。
+pub const OUTPUT_MARKER: &str = ".ksmm-output";
+
+/// `ksmm init` 生成的默认 .ksmm/build.conf
+pub const DEFAULT_BUILD_CONF: &str = include_str!("templates/common/.ksmm/build.conf");
+
+/// 构建计划中的操作类型
+#[derive(Debug, Clone)]
+pub enum OperationKind {
+    CreateDir,
+    CopyFile,
+    /// 在构建目录中重建符号链接，附带链接的目标
+    Symlink(PathBuf),
+    /// 被 `!` 规则重新包括，附带决定它的规则
+    Include(IgnoreRule),
+    /// 被忽略，附带决定它的规则
+    Ignore(IgnoreRule),
+}
+
+#[derive(Debug, Clone)]
+pub struct FileOperation {
+    pub src: PathBuf,
+    pub dst: PathBuf,
+    pub kind: OperationKind,
+}
+
+/// 从项目目录到构建目录的复制计划
+#[derive(Debug, Clone)]
+pub struct BuildPlan {
+    pub operations: Vec<FileOperation>,
+    /// 没有匹配项目中任何路径的规则，按读取顺序排列
+    pub unmatched_rules: Vec<IgnoreRule>,
+    build_dir: PathBuf,
+}
+
+impl BuildPlan {
+    /// 计算复制计划，`variant` 的打包规则在 build.conf 之后，覆盖目录中的内容最后复制
+    pub fn new(project: &Project, variant: Option<&Variant>) -> Result<Self, KsmmError> {
+        // 根目录的 .gitignore，子目录中的 .gitignore 在遍历时加入
+        let gitignore = IgnoreRules::load(project.root(), Path::new(".gitignore"), "")?;
+
+        // .ksmm/build.conf 在所有 .gitignore 之后，优先级更高
+        let mut build_conf = IgnoreRules::load(project.root(), Path::new(BUILD_CONF), "")?;
+        if let Some(variant) = variant {
+            build_conf.extend(variant.rules()?);
+        }
+
+        // 收集所有要处理的文件和目录
+        let canonical_root = fs::canonicalize(project.root())
+            .map_err(|e| KsmmError::io(tr!(build_copy_failed, e), e))?;
+        // .ksmm、位于项目中的构建目录与发布目录，以及所有变体的覆盖目录不会被打包
+        let root = config::normalize_path(project.root());
+        let overlays = project.variants().into_iter().filter_map(|variant| variant.overlay_dir(project.root()));
+        let skip = [project.ksmm_dir(), project.build_dir(), project.release_dir()]
+            .into_iter()
+            .chain(overlays)
+            .filter_map(|dir| {
+                let rel = config::normalize_path(&dir).strip_prefix(&root).ok()?.to_string_lossy().replace('\\', "/");
+                Some(rel)
+            })
+            .collect();
+        let mut walker = Walker {
+            root: project.root(),
+            skip,
+            follow_symlinks: project.config().build.follow_symlinks,
+            ancestors: vec![canonical_root.clone()],
+            canonical_root,
+            loaded: gitignore.rules().iter().chain(build_conf.rules()).map(|rule| (rule.clone(), false)).collect(),
+            gitignore,
+            build_conf,
+            operations: vec![FileOperation {
+                src: project.root().to_path_buf(),
+                dst: project.build_dir(),
+                kind: OperationKind::CreateDir,
+            }],
+            links: Vec::new(),
+        };
+        walker.collect_dir("", project.root(), &project.build_dir())?;
+        let mut operations = walker.operations;
+        let mut links = walker.links;
+        let unmatched_rules = walker.loaded.into_iter().filter(|(_, matched)| !matched).map(|(rule, _)| rule).collect();
+
+        // 覆盖目录中的操作排在同类操作之后，执行时替换已复制的同名文件
+        if let Some(variant) = variant
+            && let Some(overlay) = variant.overlay_dir(project.root())
+        {
+            if !overlay.is_dir() {
+                return Err(KsmmError::Config(tr!(variant_overlay_missing, variant.name, overlay.display())));
+            }
+            let base = fs::canonicalize(&overlay).map_err(|e| KsmmError::io(tr!(build_copy_failed, e), e))?;
+            collect_overlay(&base, "", &overlay, &project.build_dir(), &mut operations, &mut links)?;
+        }
+
+        check_links(&links, &operations, &project.build_dir())?;
+        sort_operations(&mut operations);
+        Ok(BuildPlan { operations, unmatched_rules, build_dir: project.build_dir() })
+    }
+
+    /// 加入 cargo 构建的可执行文件，它们排在同类操作之后，替换项目中同名的文件
+    ///
+    /// 文件在执行计划时才需要存在，因此可以在运行 cargo 之前计算计划。
+    pub fn add_native(&mut self, binaries: &[NativeBinary], build_dir: &Path) {
+        for binary in binaries {
+            let dst = build_dir.join(&binary.dest);
+            let dirs: Vec<&Path> =
+                dst.ancestors().skip(1).take_while(|dir| dir.starts_with(build_dir) && *dir != build_dir).collect();
+            // 从外到内创建目录
+            for dir in dirs.into_iter().rev() {
+                let exists = self.operations.iter().any(|op| matches!(op.kind, OperationKind::CreateDir) && op.dst == dir);
+                if !exists {
+                    self.operations.push(FileOperation {
+                        src: binary.src.clone(),
+                        dst: dir.to_path_buf(),
+                        kind: OperationKind::CreateDir,
+                    });
+                }
+            }
+            self.operations.push(FileOperation { src: binary.src.clone(), dst, kind: OperationKind::CopyFile });
+        }
+        sort_operations(&mut self.operations);
+    }
+
+    /// 执行计划，每完成一个操作调用一次 `on_operation`
+    pub fn execute(&self, mut on_operation: impl FnMut(&FileOperation)) -> Result<(), KsmmError> {
+        let copy_failed = |e: io::Error| KsmmError::io(tr!(build_copy_failed, e), e);
+
+        for op in &self.operations {
+            // 覆盖目录中的文件替换已复制的同名文件或链接，不能写入链接指向的文件
+            if matches!(op.kind, OperationKind::CopyFile | OperationKind::Symlink(_))
+                && fs::symlink_metadata(&op.dst).is_ok_and(|metadata| !metadata.is_dir())
+            {
+                fs::remove_file(&op.dst).map_err(copy_failed)?;
+            }
+            match op.kind {
+                OperationKind::CreateDir if op.dst == self.build_dir => create_output_dir(&op.dst).map_err(copy_failed)?,
+                OperationKind::CreateDir => fs::create_dir_all(&op.dst).map_err(copy_failed)?,
+                OperationKind::CopyFile => {
+                    fs::copy(&op.src, &op.dst).map_err(copy_failed)?;
+                }
+                OperationKind::Symlink(ref target) => create_symlink(target, &op.dst).map_err(copy_failed)?,
+                OperationKind::Include(_) | OperationKind::Ignore(_) => {}
+            }
+            on_operation(op);
+        }
+        Ok(())
+    }
+}
+
+/// 将构建目录打包为发布 zip
+///
+/// 条目按路径排序（module.prop 在最前），修改时间与压缩级别统一，同样的构建目录总是得到字节相同的 zip。
+/// 文件权限取自源文件，目录为 0755，`[permissions]` 中的模式可以覆盖两者。
+#[derive(Debug, Clone)]
+pub struct Packager {
+    build_dir: PathBuf,
+    release_dir: PathBuf,
+    timestamp: Option<i64>,
+    permissions: PermissionMap,
+    artifact_name: String,
+    compression_level: u8,
+    commit: String,
+    variant: Option<String>,
+}
+
+impl Packager {
+    /// 目录、文件名模板与压缩级别取自项目配置
+    pub fn new(project: &Project) -> Self {
+        Self::from_config(project.root(), project.config())
+    }
+
+    /// 不打开项目时使用，目录相对于 `root`
+    pub fn from_config(root: &Path, config: &Config) -> Self {
+        let build = &config.build;
+        Packager {
+            build_dir: root.join(config::expand_home(&build.build_dir)),
+            release_dir: root.join(config::expand_home(&build.release_dir)),
+            timestamp: None,
+            permissions: PermissionMap::default(),
+            artifact_name: build.artifact_name.clone(),
+            compression_level: build.compression_level,
+            commit: "unknown".to_string(),
+            variant: None,
+        }
+    }
+
+    /// 设置所有条目的修改时间（Unix 时间戳），未设置时使用 zip 能表示的最早时间 1980-01-01
+    pub fn timestamp(mut self, timestamp: Option<i64>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// 设置覆盖文件权限的模式表
+    pub fn permissions(mut self, permissions: PermissionMap) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// 设置文件名中 `{commit}` 的值，默认为 `unknown`
+    pub fn commit(mut self, commit: &str) -> Self {
+        self.commit = commit.to_string();
+        self
+    }
+
+    /// 设置文件名中 `{variant}` 的值，未设置时为空
+    pub fn variant(mut self, variant: Option<&str>) -> Self {
+        self.variant = variant.map(str::to_string);
+        self
+    }
+
+    /// 按 `build.artifact_name` 生成发布 zip 的文件名，`signed` 决定 `{signed}` 的值
+    ///
+    /// `{date}` 取自 [`Packager::timestamp`]（UTC 的 `YYYYMMDD`），未设置时为当前日期。
+    /// 模板中没有 `{signed}` 时签名前后的文件名相同，签名后的 zip 替换未签名的版本。
+    pub fn artifact_name(&self, prop: &ModuleProp, signed: bool) -> Result<String, KsmmError> {
+        let version_code = prop.version_code()?.to_string();
+        let date = self
+            .timestamp
+            .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+            .unwrap_or_else(chrono::Utc::now)
+            .format("%Y%m%d")
+            .to_string();
+        let values = [
+            ("id", prop.id()?),
+            ("version", prop.version()?),
+            ("versionCode", &version_code),
+            ("commit", &self.commit),
+            ("date", &date),
+            ("variant", self.variant.as_deref().unwrap_or_default()),
+            ("signed", if signed { SIGNED_SUFFIX } else { "" }),
+        ];
+        let lookup = |name: &str| values.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+        let name = config::expand_template(&self.artifact_name, lookup).map_err(|e| {
+            let reason = match e {
+                TemplateError::Unclosed => tr!(build_artifact_name_unclosed).to_string(),
+                TemplateError::Unknown(name) => tr!(build_artifact_name_unknown, name, ARTIFACT_PLACEHOLDERS.join(", ")),
+            };
+            KsmmError::Config(tr!(build_bad_artifact_name, self.artifact_name, reason))
+        })?;
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return Err(KsmmError::Config(tr!(build_bad_artifact_name, self.artifact_name, tr!(build_artifact_name_path))));
+        }
+        Ok(name)
+    }
+
+    /// 由未签名的文件名得到签名后的文件名，用于 `ksmm sign` 签名已有的文件
+    ///
+    /// 文件名与 `build.artifact_name` 匹配时在 `{signed}` 的位置插入 `_signed`，与 `ksmm build` 签名时的文件名一致；
+    /// 不匹配或模板中没有 `{signed}` 时插入到 `.zip` 扩展名之前（没有时在末尾），签名后的文件不会覆盖输入文件。
+    pub fn signed_artifact_name(&self, unsigned: &str) -> String {
+        // 其他占位符匹配任意文本，`{signed}` 在未签名的文件名中为空
+        let pattern = config::expand_template(&self.artifact_name, |name| match name {
+            "signed" => Some("\u{1}"),
+            _ => Some("\u{2}"),
+        });
+        let regex = pattern.ok().and_then(|pattern| {
+            let mut expr = String::from("^");
+            for c in pattern.chars() {
+                match c {
+                    '\u{1}' => expr.push_str("()"),
+                    '\u{2}' => expr.push_str(".*"),
+                    c => expr.push_str(&regex::escape(&c.to_string())),
+                }
+            }
+            expr.push('$');
+            regex::Regex::new(&expr).ok()
+        });
+        if let Some(captures) = regex.as_ref().and_then(|regex| regex.captures(unsigned))
+            && captures.len() > 1
+        {
+            let mut name = unsigned.to_string();
+            for group in captures.iter().skip(1).flatten().collect::<Vec<_>>().into_iter().rev() {
+                name.insert_str(group.start(), SIGNED_SUFFIX);
+            }
+            return name;
+        }
+        match unsigned.strip_suffix(".zip") {
+            Some(stem) => format!("{}{}.zip", stem, SIGNED_SUFFIX),
+            None => format!("{}{}", unsigned, SIGNED_SUFFIX),
+        }
+    }
+
+    /// 发布目录中 zip 的路径
+    pub fn artifact_path(&self, prop: &ModuleProp, signed: bool) -> Result<PathBuf, KsmmError> {
+        Ok(self.release_dir.join(self.artifact_name(prop, signed)?))
+    }
+
+    /// 打包并返回未签名 zip 的路径
+    pub fn package(&self, prop: &ModuleProp) -> Result<PathBuf, KsmmError> {
+        create_output_dir(&self.release_dir)
+            .map_err(|e| KsmmError::io(tr!(build_create_release_dir_failed, e), e))?;
+        let zip_path = self.artifact_path(prop, false)?;
+        self.package_to(&zip_path)?;
+        Ok(zip_path)
+    }
+
+    /// 打包到指定路径
+    pub fn package_to(&self, zip_path: &Path) -> Result<(), KsmmError> {
+        self.write_zip(zip_path).map_err(|e| KsmmError::Zip(tr!(build_zip_failed, e)))
+    }
+
+    fn write_zip(&self, zip_path: &Path) -> zip::result::ZipResult<()> {
+        let zip_file = fs::File::create(zip_path)?;
+        let mut zip = zip::ZipWriter::new(zip_file);
+
+        let options: FileOptions<'_, ()> = match self.compression_level {
+            0 => FileOptions::default().compression_method(zip::CompressionMethod::Stored),
+            level => FileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated)
+                .compression_level(Some(level.into())),
+        };
+        let options = options.last_modified_time(zip_time(self.timestamp));
+
+        let mut entries = Vec::new();
+        collect_zip_entries(&self.build_dir, "", &mut entries)?;
+        entries.retain(|(name, _)| name != OUTPUT_MARKER);
+        entries.sort_by(|(a, _), (b, _)| (a != "module.prop", a).cmp(&(b != "module.prop", b)));
+
+        for (name, path) in entries {
+            if path.is_symlink() {
+                let target = fs::read_link(&path)?;
+                zip.add_symlink(name, target.to_string_lossy().replace('\\', "/"), options.unix_permissions(0o777))?;
+            } else if path.is_dir() {
+                let mode = self.permissions.mode_for(name.trim_end_matches('/'), true).unwrap_or(0o755);
+                zip.add_directory(name, options.unix_permissions(mode))?;
+            } else {
+                let mode = match self.permissions.mode_for(&name, false) {
+                    Some(mode) => mode,
+                    None => file_mode(&path)?,
+                };
+                zip.start_file(name, options.unix_permissions(mode))?;
+                let mut f = fs::File::open(&path)?;
+                io::copy(&mut f, &mut zip)?;
+            }
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+}
+
+/// 比较两个 zip，返回第一个不同的条目名；条目相同但字节不同时返回 `Some("")`，完全相同时返回 `None`
+pub fn compare_zips(a: &Path, b: &Path) -> Result<Option<String>, KsmmError> {
+    let read = |path: &Path| fs::read(path).map_err(|e| KsmmError::io(tr!(read_file_failed, path.display(), e), e));
+    let (a_bytes, b_bytes) = (read(a)?, read(b)?);
+    if a_bytes == b_bytes {
+        return Ok(None);
+    }
+
+    let open = |bytes: Vec<u8>| {
+        zip::ZipArchive::new(io::Cursor::new(bytes)).map_err(|e| KsmmError::Zip(tr!(build_zip_failed, e)))
+    };
+    let (mut a_zip, mut b_zip) = (open(a_bytes)?, open(b_bytes)?);
+    for index in 0..a_zip.len().max(b_zip.len()) {
+        let a_entry = zip_entry_digest(&mut a_zip, index);
+        let b_entry = zip_entry_digest(&mut b_zip, index);
+        if a_entry != b_entry {
+            let name = a_entry.or(b_entry).map(|entry| entry.name).unwrap_or_default();
+            return Ok(Some(name));
+        }
+    }
+    Ok(Some(String::new()))
+}
+
+/// 条目的名称与影响输出字节的元数据
+#[derive(PartialEq)]
+struct ZipEntryDigest {
+    name: String,
+    crc32: u32,
+    compressed_size: u64,
+    unix_mode: Option<u32>,
+    last_modified: Option<zip::DateTime>,
+}
+
+fn zip_entry_digest(zip: &mut zip::ZipArchive<io::Cursor<Vec<u8>>>, index: usize) -> Option<ZipEntryDigest> {
+    let entry = zip.by_index_raw(index).ok()?;
+    Some(ZipEntryDigest {
+        name: entry.name().to_string(),
+        crc32: entry.crc32(),
+        compressed_size: entry.compressed_size(),
+        unix_mode: entry.unix_mode(),
+        last_modified: entry.last_modified(),
+    })
+}
+
+/// 将 Unix 时间戳转换为 zip 的修改时间，超出 zip 能表示的范围时使用 1980-01-01
+fn zip_time(timestamp: Option<i64>) -> zip::DateTime {
+    timestamp
+        .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+        .and_then(|time| {
+            zip::DateTime::from_date_and_time(
+                u16::try_from(time.year()).ok()?,
+                time.month() as u8,
+                time.day() as u8,
+                time.hour() as u8,
+                time.minute() as u8,
+                time.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// 遍历项目目录收集操作，同时维护当前目录生效的 .gitignore 规则
+struct Walker<'a> {
+    root: &'a Path,
+    /// 不处理的路径，以 `/` 分隔
+    skip: Vec<String>,
+    canonical_root: PathBuf,
+    follow_symlinks: bool,
+    /// 正在遍历的目录的真实路径，用于发现跟随符号链接时的循环
+    ancestors: Vec<PathBuf>,
+    gitignore: IgnoreRules,
+    build_conf: IgnoreRules,
+    operations: Vec<FileOperation>,
+    /// 读取过的所有规则，以及是否匹配过路径
+    loaded: Vec<(IgnoreRule, bool)>,
+    /// 作为链接打包的符号链接
+    links: Vec<PackedLink>,
+}
+
+impl Walker<'_> {
+    /// 检查符号链接并返回其目标：目标必须存在并位于项目目录中，跟随时也不会打包项目之外的内容
+    ///
+    /// 不跟随时返回写入 zip 的目标（见 [`link_target`]），并记录下来，计划完成后确认目标会被打包。
+    fn check_symlink(&mut self, rel: &str, src: &Path) -> Result<PathBuf, KsmmError> {
+        let target = fs::read_link(src).map_err(|e| KsmmError::io(tr!(build_copy_failed, e), e))?;
+        let Ok(resolved) = fs::canonicalize(src) else {
+            return Err(KsmmError::Build(tr!(build_symlink_dangling, rel, target.display())));
+        };
+        if self.follow_symlinks {
+            if !resolved.starts_with(&self.canonical_root) {
+                return Err(KsmmError::Build(tr!(build_symlink_escapes, rel, target.display())));
+            }
+            return Ok(target);
+        }
+        let link = link_target(rel, &target, &resolved, &self.canonical_root)?;
+        let packed = link.target.clone();
+        self.links.push(link);
+        Ok(packed)
+    }
+
+    /// 处理目录中的内容，`rel` 是以 `/` 分隔的项目内路径，根目录为空字符串
+    fn collect_dir(&mut self, rel: &str, src: &Path, dst: &Path) -> Result<(), KsmmError> {
+        // 跟随符号链接时，链接指向正在遍历的目录会无限递归
+        if !rel.is_empty() && self.follow_symlinks {
+            let real = fs::canonicalize(src).map_err(|e| KsmmError::io(tr!(build_copy_failed, e), e))?;
+            if self.ancestors.contains(&real) {
+                return Err(KsmmError::Build(tr!(build_symlink_loop, rel)));
+            }
+            self.ancestors.push(real);
+        }
+        // 子目录中的 .gitignore 只对该目录生效
+        let depth = self.gitignore.len();
+        if !rel.is_empty() {
+            let rules = IgnoreRules::load(self.root, &Path::new(rel).join(".gitignore"), rel)?;
+            self.loaded.extend(rules.rules().iter().map(|rule| (rule.clone(), false)));
+            self.gitignore.extend(rules);
+        }
+
+        let copy_failed = |e: io::Error| KsmmError::io(tr!(build_copy_failed, e), e);
+        for entry in fs::read_dir(src).map_err(copy_failed)? {
+            let entry = entry.map_err(copy_failed)?;
+            let file_name = entry.file_name();
+            let name = file_name.to_string_lossy();
+            let path = if rel.is_empty() { name.into_owned() } else { format!("{}/{}", rel, name) };
+            if self.skip.contains(&path) {
+                continue;
+            }
+            self.collect(&path, &entry.path(), &dst.join(&file_name))?;
+        }
+
+        self.gitignore.truncate(depth);
+        if !rel.is_empty() && self.follow_symlinks {
+            self.ancestors.pop();
+        }
+        Ok(())
+    }
+
+    fn collect(&mut self, rel: &str, src: &Path, dst: &Path) -> Result<(), KsmmError> {
+        let copy_failed = |e: io::Error| KsmmError::io(tr!(build_copy_failed, e), e);
+        let is_symlink = fs::symlink_metadata(src).map_err(copy_failed)?.file_type().is_symlink();
+        // 与 git 相同，不跟随的符号链接按文件匹配规则
+        let is_dir = if is_symlink && !self.follow_symlinks { false } else { src.is_dir() };
+        let operation = |kind| FileOperation {
+            src: src.to_path_buf(),
+            dst: dst.to_path_buf(),
+            kind,
+        };
+
+        for (rule, matched) in &mut self.loaded {
+            if !*matched && rule.matches(rel, is_dir) {
+                *matched = true;
+            }
+        }
+
+        // 与 git 相同，最后一条匹配的规则决定是否忽略
+        let rule = self.build_conf.decide(rel, is_dir).or_else(|| self.gitignore.decide(rel, is_dir));
+        if let Some(rule) = rule {
+            if !rule.negated {
+                self.operations.push(operation(OperationKind::Ignore(rule.clone())));
+                return Ok(());
+            }
+            self.operations.push(operation(OperationKind::Include(rule.clone())));
+        }
+
+        if is_symlink {
+            let target = self.check_symlink(rel, src)?;
+            if !self.follow_symlinks {
+                self.operations.push(operation(OperationKind::Symlink(target)));
+                return Ok(());
+            }
+        }
+
+        if is_dir {
+            // 收集创建目录的操作，再递归处理目录内容
+            self.operations.push(operation(OperationKind::CreateDir));
+            self.collect_dir(rel, src, dst)
+        } else {
+            // 收集复制文件的操作
+            self.operations.push(operation(OperationKind::CopyFile));
+            Ok(())
+        }
+    }
+}
+
+/// 作为链接打包的符号链接
+#[derive(Debug)]
+struct PackedLink {
+    /// 链接在模块中的路径，以 `/` 分隔
+    rel: String,
+    /// 写入 zip 的目标
+    target: PathBuf,
+    /// 目标在模块中的真实路径，以 `/` 分隔，根目录为空字符串
+    resolved: String,
+}
+
+/// 计算不跟随的符号链接写入 zip 的目标，`resolved` 是链接的真实路径，必须位于 `canonical_base` 中
+///
+/// 只经过模块内部的相对目标原样保留；绝对路径（例如构建机上的项目路径）或经过 `base` 之外的相对目标
+/// 改写为从链接所在目录到真实路径的相对路径，安装到设备上之后仍然指向模块中的同一个文件。
+fn link_target(rel: &str, target: &Path, resolved: &Path, canonical_base: &Path) -> Result<PackedLink, KsmmError> {
+    let Ok(resolved_rel) = resolved.strip_prefix(canonical_base) else {
+        return Err(KsmmError::Build(tr!(build_symlink_escapes, rel, target.display())));
+    };
+    let resolved_rel = resolved_rel.to_string_lossy().replace('\\', "/");
+    let mut parent: Vec<&str> = rel.split('/').collect();
+    parent.pop();
+
+    let target = if target.is_relative() && stays_inside(&parent, target) {
+        target.to_path_buf()
+    } else {
+        relative_path(&parent, &resolved_rel)
+    };
+    Ok(PackedLink { rel: rel.to_string(), target, resolved: resolved_rel })
+}
+
+/// 从目录 `parent` 出发按字面解析相对路径 `target`，是否始终不离开根目录
+fn stays_inside(parent: &[&str], target: &Path) -> bool {
+    let mut depth = parent.len();
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// 从目录 `from` 到路径 `to` 的相对路径，两者都相对于同一个根目录
+fn relative_path(from: &[&str], to: &str) -> PathBuf {
+    let to: Vec<&str> = to.split('/').filter(|part| !part.is_empty()).collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut path = PathBuf::new();
+    for _ in common..from.len() {
+        path.push("..");
+    }
+    for part in &to[common..] {
+        path.push(part);
+    }
+    if path.as_os_str().is_empty() {
+        path.push(".");
+    }
+    path
+}
+
+/// 确认每个链接的目标都会被打包，否则安装后链接会失效
+fn check_links(links: &[PackedLink], operations: &[FileOperation], build_dir: &Path) -> Result<(), KsmmError> {
+    let packaged: HashSet<String> = operations
+        .iter()
+        .filter(|op| matches!(op.kind, OperationKind::CreateDir | OperationKind::CopyFile | OperationKind::Symlink(_)))
+        .filter_map(|op| op.dst.strip_prefix(build_dir).ok())
+        .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+        .collect();
+    match links.iter().find(|link| !packaged.contains(&link.resolved)) {
+        Some(link) => Err(KsmmError::Build(tr!(build_symlink_not_packaged, link.rel, link.resolved))),
+        None => Ok(()),
+    }
+}
+
+/// 排序操作：先包括，再忽略；先目录，再文件，同类操作保持原来的顺序
+fn sort_operations(operations: &mut [FileOperation]) {
+    operations.sort_by(|a, b| {
+        // 首先按类型排序：包括 > 忽略
+        let a_is_include = matches!(a.kind, OperationKind::Include(_));
+        let b_is_include = matches!(b.kind, OperationKind::Include(_));
+
+        if a_is_include != b_is_include {
+            return b_is_include.cmp(&a_is_include); // 包括优先
+        }
+
+        // 然后按操作类型排序：创建目录 > 复制文件 > 忽略
+        let a_priority = match &a.kind {
+            OperationKind::CreateDir => 0,
+            OperationKind::CopyFile | OperationKind::Symlink(_) => 1,
+            OperationKind::Include(_) => 2,
+            OperationKind::Ignore(_) => 3,
+        };
+        let b_priority = match &b.kind {
+            OperationKind::CreateDir => 0,
+            OperationKind::CopyFile | OperationKind::Symlink(_) => 1,
+            OperationKind::Include(_) => 2,
+            OperationKind::Ignore(_) => 3,
+        };
+
+        a_priority.cmp(&b_priority)
+    });
+}
+
+/// 收集覆盖目录中的内容，原样复制到构建目录，不经过打包规则
+///
+/// `base` 是覆盖目录的真实路径，其中的符号链接与项目中的链接一样检查，目标必须位于覆盖目录中。
+fn collect_overlay(
+    base: &Path,
+    rel: &str,
+    src: &Path,
+    dst: &Path,
+    operations: &mut Vec<FileOperation>,
+    links: &mut Vec<PackedLink>,
+) -> Result<(), KsmmError> {
+    let copy_failed = |e: io::Error| KsmmError::io(tr!(build_copy_failed, e), e);
+    let mut entries = fs::read_dir(src).map_err(copy_failed)?.collect::<Result<Vec<_>, _>>().map_err(copy_failed)?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let (src, dst) = (entry.path(), dst.join(entry.file_name()));
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let path = if rel.is_empty() { name.into_owned() } else { format!("{}/{}", rel, name) };
+        let file_type = entry.file_type().map_err(copy_failed)?;
+        if file_type.is_symlink() {
+            // 与项目中的链接相同，目标必须位于覆盖目录中，绝对路径改写为相对路径
+            let target = fs::read_link(&src).map_err(copy_failed)?;
+            let Ok(resolved) = fs::canonicalize(&src) else {
+                return Err(KsmmError::Build(tr!(build_symlink_dangling, path, target.display())));
+            };
+            let link = link_target(&path, &target, &resolved, base)?;
+            operations.push(FileOperation { src, dst, kind: OperationKind::Symlink(link.target.clone()) });
+            links.push(link);
+        } else if file_type.is_dir() {
+            operations.push(FileOperation { src: src.clone(), dst: dst.clone(), kind: OperationKind::CreateDir });
+            collect_overlay(base, &path, &src, &dst, operations, links)?;
+        } else {
+            operations.push(FileOperation { src, dst, kind: OperationKind::CopyFile });
+        }
+    }
+    Ok(())
+}
+
+/// 创建符号链接；Windows 上按目标是否为目录选择链接类型
+#[cfg(unix)]
+pub fn create_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+pub fn create_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    if link.parent().is_some_and(|parent| parent.join(target).is_dir()) {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+/// 文件本身的权限，不支持权限位的平台上使用 0644
+#[cfg(unix)]
+fn file_mode(path: &Path) -> io::Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(fs::metadata(path)?.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn file_mode(_path: &Path) -> io::Result<u32> {
+    Ok(0o644)
+}
+
+/// 创建构建目录或发布目录，并写入 [`OUTPUT_MARKER`]
+pub(crate) fn create_output_dir(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let marker = dir.join(OUTPUT_MARKER);
+    if !marker.exists() {
+        fs::write(marker, "")?;
+    }
+    Ok(())
+}
+
+/// 收集构建目录中的所有条目，条目名以 `/` 分隔
+fn collect_zip_entries(dir: &Path, prefix: &str, entries: &mut Vec<(String, PathBuf)>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+
+        if entry.file_type()?.is_dir() {
+            collect_zip_entries(&path, &format!("{}/", name), entries)?;
+            entries.push((format!("{}/", name), path));
+        } else {
+            entries.push((name, path));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// 只有 module.prop 与 system/etc/example.conf 的最小项目
+    fn project_dir() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("module.prop"), "id=test\nversion=v1.0.0\nversionCode=1\n").unwrap();
+        fs::create_dir_all(dir.path().join("system/etc")).unwrap();
+        fs::write(dir.path().join("system/etc/example.conf"), "keep").unwrap();
+        dir
+    }
+
+    fn symlink_target(plan: &BuildPlan, project: &Project, rel: &str) -> Option<PathBuf> {
+        let dst = project.build_dir().join(rel);
+        plan.operations.iter().find(|op| op.dst == dst).and_then(|op| match &op.kind {
+            OperationKind::Symlink(target) => Some(target.clone()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn relative_path_walks_up_to_common_parent() {
+        assert_eq!(relative_path(&["system", "etc"], "system/etc/example.conf"), PathBuf::from("example.conf"));
+        assert_eq!(relative_path(&["system", "etc"], "system/bin/tool"), PathBuf::from("../bin/tool"));
+        assert_eq!(relative_path(&[], "system/etc"), PathBuf::from("system/etc"));
+        assert_eq!(relative_path(&["system"], "system"), PathBuf::from("."));
+    }
+
+    #[test]
+    fn stays_inside_rejects_leaving_root() {
+        assert!(stays_inside(&["system", "etc"], Path::new("../../module.prop")));
+        assert!(!stays_inside(&["system", "etc"], Path::new("../../../module.prop")));
+        assert!(!stays_inside(&["system"], Path::new("/system/etc")));
+    }
+
+    #[test]
+    fn absolute_link_inside_project_is_rewritten() {
+        let dir = project_dir();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        create_symlink(&root.join("system/etc/example.conf"), &dir.path().join("system/etc/abs.conf")).unwrap();
+        create_symlink(Path::new("../etc/example.conf"), &dir.path().join("system/etc/rel.conf")).unwrap();
+
+        let project = Project::open(dir.path()).unwrap();
+        let plan = BuildPlan::new(&project, None).unwrap();
+        assert_eq!(symlink_target(&plan, &project, "system/etc/abs.conf"), Some(PathBuf::from("example.conf")));
+        assert_eq!(symlink_target(&plan, &project, "system/etc/rel.conf"), Some(PathBuf::from("../etc/example.conf")));
+    }
+
+    #[test]
+    fn link_outside_project_is_rejected() {
+        let dir = project_dir();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret"), "x").unwrap();
+        create_symlink(&outside.path().join("secret"), &dir.path().join("system/etc/secret")).unwrap();
+
+        let project = Project::open(dir.path()).unwrap();
+        assert!(matches!(BuildPlan::new(&project, None), Err(KsmmError::Build(_))));
+    }
+
+    #[test]
+    fn link_to_unpackaged_path_is_rejected() {
+        let dir = project_dir();
+        fs::write(dir.path().join(".gitignore"), "notes.txt\n").unwrap();
+        fs::write(dir.path().join("notes.txt"), "x").unwrap();
+        create_symlink(Path::new("../../notes.txt"), &dir.path().join("system/etc/notes")).unwrap();
+
+        let project = Project::open(dir.path()).unwrap();
+        assert!(matches!(BuildPlan::new(&project, None), Err(KsmmError::Build(_))));
+    }
+
+    #[test]
+    fn overlay_links_are_checked_against_overlay() {
+        let dir = project_dir();
+        fs::create_dir_all(dir.path().join(".ksmm")).unwrap();
+        fs::write(dir.path().join(".ksmm/ksmm.toml"), "[variants.cn]\noverlay = \"overlay\"\n").unwrap();
+        fs::create_dir_all(dir.path().join("overlay/system/etc")).unwrap();
+        fs::write(dir.path().join("overlay/system/etc/cn.conf"), "cn").unwrap();
+        let overlay = fs::canonicalize(dir.path().join("overlay")).unwrap();
+        create_symlink(&overlay.join("system/etc/cn.conf"), &dir.path().join("overlay/system/etc/abs.conf")).unwrap();
+
+        let project = Project::open(dir.path()).unwrap();
+        let variant = project.variant("cn").unwrap();
+        let plan = BuildPlan::new(&project, Some(&variant)).unwrap();
+        assert_eq!(symlink_target(&plan, &project, "system/etc/abs.conf"), Some(PathBuf::from("cn.conf")));
+
+        // 指向覆盖目录之外的项目文件
+        create_symlink(Path::new("../../../system/etc/example.conf"), &dir.path().join("overlay/system/etc/out.conf")).unwrap();
+        assert!(matches!(BuildPlan::new(&project, Some(&variant)), Err(KsmmError::Build(_))));
+    }
+
+    fn packager(template: &str) -> Packager {
+        let mut config = Config::default();
+        config.build.artifact_name = template.to_string();
+        Packager::from_config(Path::new("."), &config)
+    }
+
+    #[test]
+    fn artifact_name_expands_placeholders() {
+        let prop = ModuleProp::parse("id=demo\nversion=v1.2.0\nversionCode=12\n");
+        let default = Packager::from_config(Path::new("."), &Config::default());
+        assert_eq!(default.artifact_name(&prop, false).unwrap(), "demo-12.zip");
+        assert_eq!(default.artifact_name(&prop, true).unwrap(), "demo-12_signed.zip");
+
+        // 2026-10-17T00:00:00Z
+        let packager = packager("{id}-{version}-{commit}-{date}{variant}.zip")
+            .timestamp(Some(1_792_195_200))
+            .commit("abc1234")
+            .variant(Some("-lite"));
+        assert_eq!(packager.artifact_name(&prop, true).unwrap(), "demo-v1.2.0-abc1234-20261017-lite.zip");
+    }
+
+    #[test]
+    fn artifact_name_rejects_bad_templates() {
+        let prop = ModuleProp::parse("id=demo\nversion=v1.2.0\nversionCode=12\n");
+        for template in ["{id", "{unknown}.zip", "dir/{id}.zip", "{variant}"] {
+            assert!(matches!(packager(template).artifact_name(&prop, false), Err(KsmmError::Config(_))), "{}", template);
+        }
+    }
+
+    #[test]
+    fn followed_link_outside_project_is_rejected() {
+        let dir = project_dir();
+        fs::create_dir_all(dir.path().join(".ksmm")).unwrap();
+        fs::write(dir.path().join(".ksmm/ksmm.toml"), "[build]\nfollow_symlinks = true\n").unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret"), "x").unwrap();
+        create_symlink(&outside.path().join("secret"), &dir.path().join("system/etc/secret")).unwrap();
+
+        let project = Project::open(dir.path()).unwrap();
+        assert!(matches!(BuildPlan::new(&project, None), Err(KsmmError::Build(_))));
+
+        // 项目内部的链接照常复制内容
+        fs::remove_file(dir.path().join("system/etc/secret")).unwrap();
+        create_symlink(Path::new("example.conf"), &dir.path().join("system/etc/copy.conf")).unwrap();
+        let plan = BuildPlan::new(&project, None).unwrap();
+        let dst = project.build_dir().join("system/etc/copy.conf");
+        assert!(plan.operations.iter().any(|op| op.dst == dst && matches!(op.kind, OperationKind::CopyFile)));
+    }
+
+    #[test]
+    fn signed_artifact_name_keeps_the_unsigned_name() {
+        let default = Packager::from_config(Path::new("."), &Config::default());
+        assert_eq!(default.signed_artifact_name("demo-12.zip"), "demo-12_signed.zip");
+        assert_eq!(default.signed_artifact_name("demo-lite-20261017.zip"), "demo-lite-20261017_signed.zip");
+        assert_eq!(default.signed_artifact_name("notes.txt"), "notes.txt_signed");
+
+        let signed_first = packager("{id}{signed}-{version}.zip");
+        assert_eq!(signed_first.signed_artifact_name("demo-v1.2.0.zip"), "demo_signed-v1.2.0.zip");
+        let other_ext = packager("{id}{signed}.pkg");
+        assert_eq!(other_ext.signed_artifact_name("demo.pkg"), "demo_signed.pkg");
+        // 没有 {signed} 或文件名不匹配时插入到扩展名之前
+        let unsigned_only = packager("{id}-{version}.zip");
+        assert_eq!(unsigned_only.signed_artifact_name("demo-v1.2.0.zip"), "demo-v1.2.0_signed.zip");
+        assert_eq!(other_ext.signed_artifact_name("demo.zip"), "demo_signed.zip");
+    }
+}
@@ -0,0 +1,224 @@
+use regex::Regex;
+use std::sync::OnceLock;
+use crate::i18n::tr;
+
+/// 模块根目录中的更新日志，由 `ksmm init` 生成
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+/// 更新日志中的分组，按输出顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    /// 带有 `!` 或 `BREAKING CHANGE:` 的提交，不论类型
+    Breaking,
+    Feature,
+    Fix,
+    Performance,
+    Revert,
+    /// 不符合约定式提交格式的提交
+    Other,
+}
+
+impl ChangeKind {
+    fn title(self) -> &'static str {
+        match self {
+            ChangeKind::Breaking => tr!(changelog_breaking),
+            ChangeKind::Feature => tr!(changelog_features),
+            ChangeKind::Fix => tr!(changelog_fixes),
+            ChangeKind::Performance => tr!(changelog_performance),
+            ChangeKind::Revert => tr!(changelog_reverts),
+            ChangeKind::Other => tr!(changelog_other),
+        }
+    }
+}
+
+/// 一条会写入更新日志的提交
+#[derive(Debug, Clone)]
+pub struct ChangeEntry {
+    pub kind: ChangeKind,
+    pub scope: Option<String>,
+    pub description: String,
+    /// 短提交哈希
+    pub commit: String,
+}
+
+impl ChangeEntry {
+    /// 解析约定式提交（`feat(scope)!: 描述`）
+    ///
+    /// docs、chore、ci 等不影响模块使用者的类型返回 `None`，不符合格式的提交归入 [`ChangeKind::Other`]。
+    pub fn parse(commit: &str, subject: &str, body: &str) -> Option<Self> {
+        static CONVENTIONAL: OnceLock<Regex> = OnceLock::new();
+        let regex = CONVENTIONAL.get_or_init(|| Regex::new(r"^(\w+)(?:\(([^)]*)\))?(!)?: *(.+)$").unwrap());
+
+        let subject = subject.trim();
+        let Some(captures) = regex.captures(subject) else {
+            let kind = if subject.starts_with("Revert \"") { ChangeKind::Revert } else { ChangeKind::Other };
+            return Some(ChangeEntry { kind, scope: None, description: subject.to_string(), commit: commit.to_string() });
+        };
+
+        let breaking = captures.get(3).is_some()
+            || body.lines().any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"));
+        let kind = match captures[1].to_ascii_lowercase().as_str() {
+            _ if breaking => ChangeKind::Breaking,
+            "feat" => ChangeKind::Feature,
+            "fix" => ChangeKind::Fix,
+            "perf" => ChangeKind::Performance,
+            "revert" => ChangeKind::Revert,
+            _ => return None,
+        };
+        Some(ChangeEntry {
+            kind,
+            scope: captures.get(2).map(|m| m.as_str().trim().to_string()).filter(|s| !s.is_empty()),
+            description: captures[4].trim().to_string(),
+            commit: commit.to_string(),
+        })
+    }
+}
+
+/// 两个版本之间的提交
+#[derive(Debug, Clone)]
+pub struct ReleaseChanges {
+    /// 上一个 `v*` 标签，没有时包含全部历史
+    pub since: Option<String>,
+    /// 当前版本已有标签时为该标签，否则为 `HEAD`
+    pub until: String,
+    pub entries: Vec<ChangeEntry>,
+}
+
+/// 将提交按类型分组为 Markdown 列表，没有提交时返回空字符串
+pub fn render(entries: &[ChangeEntry]) -> String {
+    let mut kinds: Vec<ChangeKind> = entries.iter().map(|entry| entry.kind).collect();
+    kinds.sort();
+    kinds.dedup();
+
+    let mut sections = Vec::new();
+    for kind in kinds {
+        let mut section = format!("### {}\n\n", kind.title());
+        for entry in entries.iter().filter(|entry| entry.kind == kind) {
+            match &entry.scope {
+                Some(scope) => section.push_str(&format!("- **{}:** {} ({})\n", scope, entry.description, entry.commit)),
+                None => section.push_str(&format!("- {} ({})\n", entry.description, entry.commit)),
+            }
+        }
+        sections.push(section);
+    }
+    sections.join("\n")
+}
+
+/// 写入版本段落：没有该标题时插入到第一个版本标题（`## `）之前，最新的版本始终在最前面；
+/// 已有空段落时填入 `body`
+///
+/// 已有内容的段落是手写的，不会修改，此时返回 `None`；`body` 为空且标题已存在时同样返回 `None`。
+pub fn write_section(content: &str, heading: &str, body: &str) -> Option<String> {
+    let heading_line = format!("## {}", heading);
+    let body = body.trim_end();
+    // 文件不存在时按当前语言添加标题
+    let default_title = format!("# {}\n", tr!(changelog_title));
+    let content = if content.trim().is_empty() { default_title.as_str() } else { content };
+
+    // 每行的起始位置，便于定位段落
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        lines.push((offset, line));
+        offset += line.len();
+    }
+
+    if let Some(index) = lines.iter().position(|(_, line)| line.trim_end() == heading_line) {
+        let (start, heading_text) = lines[index];
+        let section_end = lines[index + 1..]
+            .iter()
+            .find(|(_, line)| line.starts_with("## "))
+            .map_or(content.len(), |(offset, _)| *offset);
+        let section_start = start + heading_text.len();
+        if body.is_empty() || !content[section_start..section_end].trim().is_empty() {
+            return None;
+        }
+
+        let mut result = content[..section_start].to_string();
+        if !result.ends_with('\n') {
+            result.push('\n');
+        }
+        result.push('\n');
+        result.push_str(body);
+        result.push('\n');
+        if section_end < content.len() {
+            result.push('\n');
+            result.push_str(&content[section_end..]);
+        }
+        return Some(result);
+    }
+
+    let mut section = format!("{}\n", heading_line);
+    if !body.is_empty() {
+        section.push('\n');
+        section.push_str(body);
+        section.push('\n');
+    }
+
+    if let Some((start, _)) = lines.iter().find(|(_, line)| line.starts_with("## ")) {
+        let mut result = content[..*start].to_string();
+        result.push_str(&section);
+        result.push('\n');
+        result.push_str(&content[*start..]);
+        return Some(result);
+    }
+
+    // 还没有任何版本标题，追加到末尾
+    let mut result = content.to_string();
+    if !result.ends_with('\n') {
+        result.push('\n');
+    }
+    result.push_str(&section);
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = "### Features\n\n- new thing (abc1234)\n";
+
+    #[test]
+    fn write_section_inserts_before_previous_versions() {
+        let content = "# Changelog\n\n## v1.0.0\n\n- first\n";
+        let result = write_section(content, "v1.1.0", BODY).unwrap();
+        assert_eq!(result, "# Changelog\n\n## v1.1.0\n\n### Features\n\n- new thing (abc1234)\n\n## v1.0.0\n\n- first\n");
+    }
+
+    #[test]
+    fn write_section_is_idempotent() {
+        let content = "# Changelog\n\n## v1.0.0\n\n- first\n";
+        let once = write_section(content, "v1.1.0", BODY).unwrap();
+        assert_eq!(write_section(&once, "v1.1.0", BODY), None);
+        assert_eq!(write_section(&once, "v1.1.0", "### Fixes\n\n- other\n"), None);
+    }
+
+    #[test]
+    fn write_section_fills_empty_section() {
+        let content = "# Changelog\n\n## v1.1.0\n\n## v1.0.0\n\n- first\n";
+        let result = write_section(content, "v1.1.0", BODY).unwrap();
+        assert_eq!(result, "# Changelog\n\n## v1.1.0\n\n### Features\n\n- new thing (abc1234)\n\n## v1.0.0\n\n- first\n");
+        assert_eq!(write_section(&result, "v1.1.0", BODY), None);
+    }
+
+    #[test]
+    fn write_section_appends_without_versions() {
+        let result = write_section("# Changelog\n", "v0.1.0", "").unwrap();
+        assert_eq!(result, "# Changelog\n## v0.1.0\n");
+        assert_eq!(write_section(&result, "v0.1.0", ""), None);
+    }
+
+    #[test]
+    fn parse_conventional_commits() {
+        let entry = ChangeEntry::parse("abc", "feat(ui)!: redesign", "").unwrap();
+        assert_eq!(entry.kind, ChangeKind::Breaking);
+        assert_eq!(entry.scope.as_deref(), Some("ui"));
+        assert_eq!(entry.description, "redesign");
+
+        let entry = ChangeEntry::parse("abc", "fix: crash", "BREAKING CHANGE: config moved").unwrap();
+        assert_eq!(entry.kind, ChangeKind::Breaking);
+        assert_eq!(ChangeEntry::parse("abc", "perf: faster", "").unwrap().kind, ChangeKind::Performance);
+        assert_eq!(ChangeEntry::parse("abc", "Update readme", "").unwrap().kind, ChangeKind::Other);
+        assert!(ChangeEntry::parse("abc", "chore: bump deps", "").is_none());
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::build::{self, OperationKind};
+use crate::error::KsmmError;
+use crate::i18n::tr;
+use crate::ignore::IgnoreRules;
+use crate::project::Project;
+
+/// KernelSU 会使用的模块根目录文件与目录，其余文件即使被打包也会被忽略
+const KNOWN_TOP_LEVEL: &[&str] = &[
+    "module.prop",
+    "system",
+    "zygisk",
+    "webroot",
+    "META-INF",
+    "customize.sh",
+    "post-fs-data.sh",
+    "post-mount.sh",
+    "service.sh",
+    "boot-completed.sh",
+    "uninstall.sh",
+    "action.sh",
+    "system.prop",
+    "sepolicy.rule",
+    "skip_mount",
+    "disable",
+    "remove",
+    "update",
+];
+
+const SHEBANG: &str = "#!/system/bin/sh";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// 一条检查结果，`file` 是相对于项目根目录的路径
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, file: impl Into<String>, line: Option<usize>, message: String) -> Self {
+        Diagnostic { severity, file: file.into(), line, message }
+    }
+
+    /// `file:line` 形式的位置
+    pub fn location(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{}", self.file, line),
+            None => self.file.clone(),
+        }
+    }
+}
+
+/// 检查项目中会导致模块在设备上出问题的内容
+pub fn check(project: &Project) -> Result<Vec<Diagnostic>, KsmmError> {
+    let mut diagnostics = Vec::new();
+
+    // module.prop 中的字段
+    let prop = project.module_prop()?;
+    for error in prop.validate() {
+        diagnostics.push(Diagnostic::new(Severity::Error, "module.prop", error.line, error.message));
+    }
+
+    // 会被打包的文件
+    let plan = project.plan_build()?;
+    let mut unknown = BTreeSet::new();
+    for op in &plan.operations {
+        if !matches!(op.kind, OperationKind::CopyFile | OperationKind::CreateDir) {
+            continue;
+        }
+        let Ok(rel) = op.src.strip_prefix(project.root()) else {
+            continue;
+        };
+
+        let mut components = rel.components();
+        if let (Some(first), None) = (components.next(), components.next()) {
+            let name = first.as_os_str().to_string_lossy();
+            if !KNOWN_TOP_LEVEL.contains(&name.as_ref()) {
+                unknown.insert(name.into_owned());
+            }
+        }
+
+        if matches!(op.kind, OperationKind::CopyFile) && rel.extension().is_some_and(|ext| ext == "sh") {
+            check_script(&op.src, &rel.to_string_lossy(), &mut diagnostics)
+                .map_err(|e| KsmmError::io(tr!(read_file_failed, op.src.display(), e), e))?;
+        }
+    }
+    for name in unknown {
+        let message = tr!(check_unknown_top_level, name);
+        diagnostics.push(Diagnostic::new(Severity::Warning, name, None, message));
+    }
+
+    // build.conf 中没有匹配任何文件的规则，默认配置中预防性的规则不算在内
+    let default_rules = IgnoreRules::parse(Path::new(build::BUILD_CONF), "", build::DEFAULT_BUILD_CONF)?;
+    for rule in &plan.unmatched_rules {
+        let is_default = default_rules.rules().iter().any(|d| d.pattern == rule.pattern && d.negated == rule.negated);
+        if rule.source == Path::new(build::BUILD_CONF) && !is_default {
+            let message = tr!(check_unused_rule, rule.pattern);
+            diagnostics.push(Diagnostic::new(Severity::Warning, build::BUILD_CONF, Some(rule.line), message));
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+/// 检查 BOM、CRLF 换行与 shebang
+fn check_script(path: &Path, file: &str, diagnostics: &mut Vec<Diagnostic>) -> io::Result<()> {
+    let content = fs::read(path)?;
+
+    let content = match content.strip_prefix(b"\xEF\xBB\xBF") {
+        Some(rest) => {
+            diagnostics.push(Diagnostic::new(Severity::Error, file, Some(1), tr!(check_script_bom).to_string()));
+            rest
+        }
+        None => &content[..],
+    };
+
+    if let Some(index) = content.windows(2).position(|w| w == b"\r\n") {
+        let line = content[..index].iter().filter(|&&b| b == b'\n').count() + 1;
+        diagnostics.push(Diagnostic::new(Severity::Error, file, Some(line), tr!(check_script_crlf).to_string()));
+    }
+
+    let first_line = content.split(|&b| b == b'\n').next().unwrap_or_default();
+    if first_line.trim_ascii_end() != SHEBANG.as_bytes() {
+        diagnostics.push(Diagnostic::new(Severity::Warning, file, Some(1), tr!(check_script_shebang, SHEBANG)));
+    }
+
+    Ok(())
+}
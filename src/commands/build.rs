@@ -1,569 +1,464 @@
+use clap::Args;
 use owo_colors::OwoColorize;
+use std::collections::BTreeMap;
+use std::env;
 use std::fs;
-use std::path::Path;
-use std::path::PathBuf;
-use std::collections::HashMap;
-use std::process::Command;
-use regex::Regex;
-use chrono::{Datelike, Timelike, Utc};
-use std::io;
-use zip::write::FileOptions;
-
-fn refresh_version_code(module_prop_path: &Path) -> Result<i32, Box<dyn std::error::Error>> {
-    // 读取并解析 module.prop，保持原始顺序
-    let module_prop_content = fs::read_to_string(module_prop_path)?;
-
-    let mut module_info = HashMap::new();
-
-    // 刷新 versionCode
-    let now = Utc::now();
-    let new_version_code = (now.year() * 1000000 + now.month() as i32 * 10000 + now.day() as i32 * 100 + now.hour() as i32) as i32;
-
-    let mut new_module_prop_content = String::new();
-    let mut version_code_updated = false;
-
-    for line in module_prop_content.lines() {
-        if let Some((key, value)) = line.split_once('=') {
-            let key = key.to_string();
-            let value = value.to_string();
-
-            if key == "versionCode" && !version_code_updated {
-                new_module_prop_content.push_str(&format!("versionCode={}\n", new_version_code));
-                module_info.insert(key, new_version_code.to_string());
-                version_code_updated = true;
-            } else {
-                new_module_prop_content.push_str(&format!("{}={}\n", key, value));
-                module_info.insert(key, value);
-            }
-        } else {
-            new_module_prop_content.push_str(&format!("{}\n", line));
-        }
-    }
-
-    // 如果没有找到 versionCode 字段，添加一个
-    if !version_code_updated {
-        new_module_prop_content.push_str(&format!("versionCode={}\n", new_version_code));
-        module_info.insert("versionCode".to_string(), new_version_code.to_string());
-    }
-
-    fs::write(module_prop_path, new_module_prop_content)?;
-    println!("{} 刷新 versionCode: {}", "[+]".green(), new_version_code);
-
-    Ok(new_version_code)
+use std::path::{Path, PathBuf};
+use std::process;
+use ksmm::build;
+use ksmm::{
+    AbiBinaries, BuildPlan, IgnoreRule, ModuleProp, NativeBinary, OperationKind, Overrides, Packager, Project, Signer,
+    UpdateJson, Variant, KsmmError,
+};
+use ksmm::i18n::tr;
+
+/// `ksmm build` 的命令行参数
+#[derive(Args, Debug, Default)]
+pub struct BuildArgs {
+    /// 只输出将被打包的文件，不修改 module.prop 与 .ksmm/
+    #[arg(long)]
+    dry_run: bool,
+
+    /// 同时说明每个路径由哪条规则决定，并列出没有匹配任何路径的规则
+    #[arg(long, requires = "dry_run")]
+    explain: bool,
+
+    /// 构建两次并确认得到字节相同的 zip
+    #[arg(long, conflicts_with = "dry_run")]
+    check_reproducible: bool,
+
+    /// 只在打包的 module.prop 中使用新的 versionCode，不修改源文件
+    #[arg(long, conflicts_with = "dry_run")]
+    no_write: bool,
+
+    /// 构建目录 (覆盖配置中的 build.build_dir)
+    #[arg(long, value_name = "DIR")]
+    build_dir: Option<PathBuf>,
+
+    /// 发布目录 (覆盖配置中的 build.release_dir)
+    #[arg(long, value_name = "DIR")]
+    release_dir: Option<PathBuf>,
+
+    /// 发布 zip 的文件名模板 (覆盖配置中的 build.artifact_name)
+    #[arg(long, value_name = "TEMPLATE")]
+    artifact_name: Option<String>,
+
+    /// 压缩级别 0-9，0 表示不压缩 (覆盖配置中的 build.compression_level)
+    #[arg(long, value_name = "LEVEL", value_parser = clap::value_parser!(u8).range(0..=9))]
+    compression_level: Option<u8>,
+
+    /// versionCode 策略，env:NAME 表示读取环境变量 NAME (覆盖配置中的 version_code)
+    #[arg(long, value_name = "STRATEGY")]
+    version_code: Option<String>,
+
+    /// 签名使用的私钥 (覆盖配置中的 sign.key)
+    #[arg(long, value_name = "FILE")]
+    key: Option<PathBuf>,
+
+    /// 只构建指定的变体
+    #[arg(long, value_name = "NAME", conflicts_with = "all_variants")]
+    variant: Option<String>,
+
+    /// 构建配置中的所有变体，每个变体生成一个 zip 与 update.json
+    #[arg(long)]
+    all_variants: bool,
 }
 
-fn parse_github_info(update_json_url: &str) -> (String, String) {
-    // 从 updateJson URL 解析 GitHub 用户名和仓库名
-    let github_regex = Regex::new(r"github\.com[\/:]([^\/]+)\/([^\/]+)").unwrap();
-    if let Some(captures) = github_regex.captures(update_json_url) {
-        let username = captures.get(1).map_or("unknown", |m| m.as_str());
-        let repo = captures.get(2).map_or("repo", |m| m.as_str()).trim_end_matches(".git");
-        (username.to_string(), repo.to_string())
-    } else {
-        ("unknown".to_string(), "repo".to_string())
+impl BuildArgs {
+    /// 命令行中设置的配置项，优先于环境变量与配置文件
+    fn overrides(&self) -> Overrides {
+        let mut overrides = Overrides::default();
+        if let Some(dir) = &self.build_dir {
+            overrides.set("build.build_dir", dir.to_string_lossy().as_ref());
+        }
+        if let Some(dir) = &self.release_dir {
+            overrides.set("build.release_dir", dir.to_string_lossy().as_ref());
+        }
+        if let Some(template) = &self.artifact_name {
+            overrides.set("build.artifact_name", template.as_str());
+        }
+        if let Some(level) = self.compression_level {
+            overrides.set("build.compression_level", i64::from(level));
+        }
+        if let Some(strategy) = &self.version_code {
+            overrides.set_version_code(strategy);
+        }
+        if let Some(key) = &self.key {
+            // 相对路径相对于当前目录，也就是项目根目录
+            overrides.set("sign.key", key.to_string_lossy().as_ref());
+        }
+        overrides
     }
 }
 
-fn get_git_commit_hash() -> String {
-    Command::new("git")
-        .args(&["rev-parse", "--short", "HEAD"])
-        .output()
-        .ok()
-        .and_then(|output| if output.status.success() {
-            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
-        } else {
-            None
-        })
-        .unwrap_or_else(|| "unknown".to_string())
+/// 按当前语言替换 `ksmm build` 参数的帮助文本
+pub fn localize(cmd: clap::Command) -> clap::Command {
+    cmd.about(tr!(help_build))
+        .mut_arg("dry_run", |a| a.help(tr!(help_build_dry_run)))
+        .mut_arg("explain", |a| a.help(tr!(help_build_explain)))
+        .mut_arg("check_reproducible", |a| a.help(tr!(help_build_check_reproducible)))
+        .mut_arg("no_write", |a| a.help(tr!(help_build_no_write)))
+        .mut_arg("build_dir", |a| a.help(tr!(help_build_build_dir)))
+        .mut_arg("release_dir", |a| a.help(tr!(help_build_release_dir)))
+        .mut_arg("artifact_name", |a| a.help(tr!(help_build_artifact_name)))
+        .mut_arg("compression_level", |a| a.help(tr!(help_build_compression_level)))
+        .mut_arg("version_code", |a| a.help(tr!(help_build_version_code)))
+        .mut_arg("key", |a| a.help(tr!(help_build_key)))
+        .mut_arg("variant", |a| a.help(tr!(help_build_variant)))
+        .mut_arg("all_variants", |a| a.help(tr!(help_build_all_variants)))
 }
 
-fn generate_update_json(module_info: &HashMap<String, String>, short_commit: &str, release_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let id = module_info.get("id").map(|s| s.as_str()).unwrap_or("unknown");
-    let version = module_info.get("version").map(|s| s.as_str()).unwrap_or("0.1.0");
-    let version_code = module_info.get("versionCode").map(|s| s.as_str()).unwrap_or("1");
-    let update_json_url = module_info.get("updateJson").map(|s| s.as_str()).unwrap_or("https://github.com/unknown/repo/releases/latest/download/update.json");
-
-    let (username, repo) = parse_github_info(update_json_url);
-
-    // 生成 update.json
-    let update_json = format!(
-        r#"{{
-  "changelog": "https://raw.githubusercontent.com/{}/{}/main/CHANGELOG.md",
-  "version": "v{}-{}",
-  "versionCode": {},
-  "zipUrl": "https://github.com/{}/{}/releases/latest/download/{}-{}.zip"
-}}"#,
-        username, repo, version, short_commit, version_code, username, repo, id, version_code
-    );
-
-    let update_json_path = release_dir.join("update.json");
-    fs::write(&update_json_path, update_json)?;
-
-    Ok(())
-}
-
-fn read_build_config(file_path: &Path) -> (Vec<String>, Vec<String>) {
-    let mut ignore_patterns = Vec::new();
-    let mut include_patterns = Vec::new();
-
-    if !file_path.exists() {
-        return (ignore_patterns, include_patterns);
+pub fn execute(args: BuildArgs) -> Result<(), KsmmError> {
+    if args.dry_run {
+        return dry_run(&args);
     }
 
-    match fs::read_to_string(file_path) {
-        Ok(content) => {
-            for line in content.lines() {
-                let line = line.trim();
-                if line.is_empty() || line.starts_with('#') {
-                    continue;
-                }
-
-                if line.starts_with('!') {
-                    // 强制包括模式
-                    include_patterns.push(line[1..].to_string());
-                } else {
-                    // 忽略模式
-                    ignore_patterns.push(line.to_string());
-                }
-            }
-        }
-        Err(_) => {}
-    }
+    println!("🔨 {}", tr!(build_start).cyan());
 
-    (ignore_patterns, include_patterns)
-}
+    let project = Project::open_with_overrides(".", &args.overrides())?;
+    let timestamp = project.source_date_epoch()?;
+    let config = project.config();
 
-fn matches_pattern(file_path: &str, pattern: &str) -> bool {
-    // 简单模式匹配实现
-    // 支持 * 通配符和目录匹配（以 / 结尾）
-    if pattern.ends_with('/') {
-        // 目录匹配
-        return file_path.starts_with(pattern) || file_path.contains(&format!("/{}", pattern.trim_end_matches('/')));
-    } else if pattern.contains('*') {
-        // 通配符匹配
-        let regex_pattern = pattern.replace('.', r"\.").replace('*', ".*");
-        if let Ok(regex) = Regex::new(&format!("^{}$", regex_pattern)) {
-            return regex.is_match(file_path);
+    // 先计算 versionCode、复制计划、update.json 的模板与签名密钥，失败时不修改任何文件
+    let native = project.native_build()?;
+    let mut prop = project.module_prop()?;
+    prop.set_version_code(project.next_version_code(&prop, &config.version_code)?);
+    let short_commit = project.git_commit_hash()?;
+    let signing_key = project.signing_key()?;
+    let mut targets: Vec<Target> = Vec::new();
+    for variant in selected_variants(&project, &args)? {
+        let mut target_prop = prop.clone();
+        if let Some(variant) = &variant {
+            variant.apply(&mut target_prop)?;
         }
-    } else {
-        // 精确匹配
-        return file_path == pattern || file_path.ends_with(&format!("/{}", pattern));
-    }
-    false
-}
-
-fn copy_files_to_build(build_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    // 确保构建目录存在
-    fs::create_dir_all(build_dir)?;
-
-    // 读取 .gitignore
-    let gitignore_patterns = read_ignore_file(Path::new(".gitignore"));
-
-    // 读取 .ksmm/build.conf
-    let (ksmm_ignore_patterns, include_patterns) = read_build_config(Path::new(".ksmm/build.conf"));
-
-    // 合并忽略模式：.ksmm/build.conf 优先级更高
-    let mut all_ignore_patterns = gitignore_patterns;
-    all_ignore_patterns.extend(ksmm_ignore_patterns);
-
-    // 收集所有要处理的文件和目录
-    let mut operations = Vec::new();
-    collect_operations(Path::new("."), build_dir, &all_ignore_patterns, &include_patterns, &mut operations)?;
-
-    // 排序操作：先包括，再忽略；先目录，再文件
-    operations.sort_by(|a, b| {
-        // 首先按类型排序：包括 > 忽略
-        let a_is_include = matches!(a.operation_type, OperationType::Include(_));
-        let b_is_include = matches!(b.operation_type, OperationType::Include(_));
-
-        if a_is_include != b_is_include {
-            return b_is_include.cmp(&a_is_include); // 包括优先
+        let packager = Packager::new(&project)
+            .timestamp(timestamp)
+            .permissions(config.permissions.clone())
+            .commit(&short_commit)
+            .variant(variant.as_ref().map(|variant| variant.name.as_str()));
+        let artifact = packager.artifact_name(&target_prop, signing_key.is_some())?;
+        // 所有变体的 zip 都放在同一个发布目录中
+        if targets.iter().any(|target| target.artifact == artifact) {
+            return Err(KsmmError::Config(tr!(variant_artifact_collision, artifact)));
         }
-
-        // 然后按操作类型排序：创建目录 > 复制文件 > 忽略
-        let a_priority = match &a.operation_type {
-            OperationType::CreateDir => 0,
-            OperationType::CopyFile => 1,
-            OperationType::Include(_) => 2,
-            OperationType::Ignore(_) => 3,
-        };
-        let b_priority = match &b.operation_type {
-            OperationType::CreateDir => 0,
-            OperationType::CopyFile => 1,
-            OperationType::Include(_) => 2,
-            OperationType::Ignore(_) => 3,
-        };
-
-        a_priority.cmp(&b_priority)
-    });
-
-    // 执行操作并输出日志
-    for op in operations {
-        match op.operation_type {
-            OperationType::CreateDir => {
-                fs::create_dir_all(&op.dst)?;
-                println!("{} 创建目录: {}", "[+]".cyan(), op.dst.display());
-            }
-            OperationType::CopyFile => {
-                fs::copy(&op.src, &op.dst)?;
-                println!("{} 复制文件: {} -> {}", "[+]".green(), op.src.display(), op.dst.display());
-            }
-            OperationType::Include(pattern) => {
-                println!("{} 文件 '{}' 匹配包括模式 '{}', 包括", "[+]".yellow(), op.src.display(), pattern);
-            }
-            OperationType::Ignore(pattern) => {
-                println!("{} 文件 '{}' 匹配忽略模式 '{}', 忽略", "[-]".red(), op.src.display(), pattern);
-            }
+        let mut plan = project.plan_build_for(variant.as_ref())?;
+        if let Some(native) = &native {
+            plan.add_native(native.binaries(), &project.build_dir());
         }
+        targets.push(Target {
+            binaries: project.abi_binaries(&plan)?,
+            plan,
+            update_json: project.update_json(&target_prop, &short_commit, &artifact)?,
+            variant,
+            prop: target_prop,
+            packager,
+            artifact,
+        });
     }
 
-    Ok(())
-}
-
-#[derive(Debug, Clone)]
-enum OperationType {
-    CreateDir,
-    CopyFile,
-    Include(String),
-    Ignore(String),
-}
-
-#[derive(Debug, Clone)]
-struct FileOperation {
-    src: PathBuf,
-    dst: PathBuf,
-    operation_type: OperationType,
-}
-
-fn collect_operations(
-    src: &Path,
-    dst: &Path,
-    ignore_patterns: &[String],
-    include_patterns: &[String],
-    operations: &mut Vec<FileOperation>,
-) -> io::Result<()> {
-    if !src.exists() {
-        return Ok(());
+    // cargo 构建失败时同样不修改任何文件
+    if let Some(native) = &native {
+        native.build(|target| println!("🦀 {}", tr!(build_native_start, target).cyan()))?;
+        println!("✅ {}", tr!(build_native_done));
     }
 
-    // 检查是否是强制包括的文件
-    if let Some(include_pattern) = is_force_include(src, include_patterns) {
-        operations.push(FileOperation {
-            src: src.to_path_buf(),
-            dst: dst.to_path_buf(),
-            operation_type: OperationType::Include(include_pattern),
-        });
+    // 前先清空build目录和release目录
+    if project.clean_build_dir()? {
+        println!("{} {}", "[+]".green(), tr!(build_clean_build_dir));
     }
-
-    // 检查是否应该忽略此文件/目录
-    if let Some(ignore_pattern) = should_ignore_file(src, ignore_patterns, include_patterns) {
-        operations.push(FileOperation {
-            src: src.to_path_buf(),
-            dst: dst.to_path_buf(),
-            operation_type: OperationType::Ignore(ignore_pattern),
-        });
-        return Ok(());
+    if project.clean_release_dir()? {
+        println!("{} {}", "[+]".green(), tr!(build_clean_release_dir));
     }
 
-    if src.is_dir() {
-        // 收集创建目录的操作
-        operations.push(FileOperation {
-            src: src.to_path_buf(),
-            dst: dst.to_path_buf(),
-            operation_type: OperationType::CreateDir,
-        });
-
-        // 递归处理目录内容
-        for entry in fs::read_dir(src)? {
-            let entry = entry?;
-            let src_path = entry.path();
-            let file_name = entry.file_name();
-            let dst_path = dst.join(&file_name);
-
-            // 跳过 .ksmm 目录本身
-            if file_name == ".ksmm" {
-                continue;
-            }
-
-            collect_operations(&src_path, &dst_path, ignore_patterns, include_patterns, operations)?;
-        }
+    // 刷新 versionCode，--no-write 时只写入构建目录中的 module.prop
+    if args.no_write {
+        println!("{} {}", "[+]".green(), tr!(build_version_code_no_write, prop.version_code()?));
     } else {
-        // 收集复制文件的操作
-        operations.push(FileOperation {
-            src: src.to_path_buf(),
-            dst: dst.to_path_buf(),
-            operation_type: OperationType::CopyFile,
-        });
+        prop.save(&project.module_prop_path())?;
+        println!("{} {}", "[+]".green(), tr!(build_refresh_version_code, prop.version_code()?));
     }
 
+    for target in &targets {
+        build_target(&project, target, signing_key.as_deref(), args.check_reproducible)?;
+    }
     Ok(())
 }
 
-fn should_ignore_file(file_path: &Path, ignore_patterns: &[String], include_patterns: &[String]) -> Option<String> {
-    let file_str = file_path.to_string_lossy();
-
-    // 首先检查是否在包括列表中（最高优先级）
-    if is_force_include(file_path, include_patterns).is_some() {
-        return None;
-    }
-
-    // 然后检查是否在忽略列表中
-    for pattern in ignore_patterns {
-        if matches_pattern(&file_str, pattern) {
-            return Some(pattern.clone());
-        }
-    }
-
-    None
+/// 一个要发布的 zip：默认构建或一个变体
+struct Target {
+    variant: Option<Variant>,
+    /// 打包的 module.prop，包含新的 versionCode 与变体的覆盖
+    prop: ModuleProp,
+    plan: BuildPlan,
+    /// `bin/<abi>/` 中按架构安装的可执行文件
+    binaries: AbiBinaries,
+    packager: Packager,
+    update_json: UpdateJson,
+    /// 最终发布的 zip 的文件名
+    artifact: String,
 }
 
-fn is_force_include(file_path: &Path, include_patterns: &[String]) -> Option<String> {
-    let file_str = file_path.to_string_lossy();
-
-    for pattern in include_patterns {
-        if matches_pattern(&file_str, pattern) {
-            return Some(pattern.clone());
+/// `--variant` 与 `--all-variants` 选择的变体，都没有指定时只有默认构建
+fn selected_variants(project: &Project, args: &BuildArgs) -> Result<Vec<Option<Variant>>, KsmmError> {
+    if args.all_variants {
+        let variants = project.variants();
+        if variants.is_empty() {
+            return Err(KsmmError::Input(tr!(variant_none_configured).to_string()));
         }
+        return Ok(variants.into_iter().map(Some).collect());
+    }
+    match &args.variant {
+        Some(name) => Ok(vec![Some(project.variant(name)?)]),
+        None => Ok(vec![None]),
     }
-
-    None
 }
 
-fn read_ignore_file(file_path: &Path) -> Vec<String> {
-    if !file_path.exists() {
-        return Vec::new();
+/// 复制、打包、签名并写入 update.json
+fn build_target(project: &Project, target: &Target, signing_key: Option<&Path>, check: bool) -> Result<(), KsmmError> {
+    if let Some(variant) = &target.variant {
+        println!("🧩 {}", tr!(build_variant_start, variant.name).cyan());
     }
 
-    match fs::read_to_string(file_path) {
-        Ok(content) => content
-            .lines()
-            .map(|line| line.trim().to_string())
-            .filter(|line| !line.is_empty() && !line.starts_with('#'))
-            .collect(),
-        Err(_) => Vec::new(),
+    // 复制文件到构建目录
+    project.clean_build_dir()?;
+    target.plan.execute(|op| match &op.kind {
+        OperationKind::CreateDir => {
+            println!("{} {}", "[+]".cyan(), tr!(build_create_dir, op.dst.display()));
+        }
+        OperationKind::CopyFile => {
+            println!("{} {}", "[+]".green(), tr!(build_copy_file, op.src.display(), op.dst.display()));
+        }
+        OperationKind::Symlink(target) => {
+            println!("{} {}", "[+]".green(), tr!(build_create_symlink, op.dst.display(), target.display()));
+        }
+        OperationKind::Include(rule) => {
+            println!("{} {}", "[+]".yellow(), tr!(build_include_file, op.src.display(), rule.pattern, rule.location()));
+        }
+        OperationKind::Ignore(rule) => {
+            println!("{} {}", "[-]".red(), tr!(build_ignore_file, op.src.display(), rule.pattern, rule.location()));
+        }
+    })?;
+    stage_generated_files(project, target)?;
+    if !target.binaries.is_empty() {
+        println!("{} {}", "[+]".green(), tr!(build_abi_installer, abi_names(&target.binaries)));
     }
-}
-
-fn package_build_to_zip(build_dir: &Path, module_info: &HashMap<String, String>) -> Result<(), Box<dyn std::error::Error>> {
-    let id = module_info.get("id").unwrap_or(&"unknown".to_string()).clone();
-    let _version = module_info.get("version").unwrap_or(&"0.1.0".to_string()).clone();
-    let version_code = module_info.get("versionCode").unwrap_or(&"1".to_string()).clone();
+    println!("✅ {}", tr!(build_done));
 
-    let release_dir = Path::new(".ksmm/release");
-
-    // 生成ZIP文件名
-    let zip_filename = format!("{}-{}.zip", id, version_code);
-    let zip_path = release_dir.join(&zip_filename);
-
-    // 创建ZIP文件
-    let zip_file = fs::File::create(&zip_path)?;
-    let mut zip = zip::ZipWriter::new(zip_file);
-
-    let options: FileOptions<'_, ()> = FileOptions::default()
-        .compression_method(zip::CompressionMethod::Deflated)
-        .unix_permissions(0o755);
+    // 打包构建产物为ZIP
+    let zip_path = target.packager.package(&target.prop)?;
+    println!("{} {}", "[+]".green(), tr!(build_created_release_file, display_path(project, &zip_path)));
 
-    // 递归添加文件到ZIP
-    add_dir_to_zip(&mut zip, build_dir, build_dir, &options)?;
+    if check {
+        check_reproducible(project, target, &zip_path)?;
+    }
 
-    zip.finish()?;
-    println!("{} 创建 .ksmm/release/{}", "[+]".green(), zip_filename);
+    // 检查并签名
+    println!("🔍 {}", tr!(build_sign_check));
+    let artifact = match signing_key {
+        Some(key) => {
+            println!("🔑 {}", tr!(build_sign_key_found));
+            let signed_path = target.packager.artifact_path(&target.prop, true)?;
+            Signer::new(key).sign_to(&zip_path, &signed_path)?;
+            println!("✅ {}", tr!(build_sign_success));
+            println!("{} {}", "[+]".green(), tr!(build_created_release_file, display_path(project, &signed_path)));
+            signed_path
+        }
+        None => {
+            println!("ℹ️ {}", tr!(build_sign_no_key));
+            zip_path
+        }
+    };
 
+    // update.json 指向最终发布的 zip，签名后为签名的版本
+    let update_json_path = project.write_update_json(&target.update_json, &artifact, target.variant.as_ref())?;
+    println!("{} {}", "[+]".green(), tr!(build_created_release_file, display_path(project, &update_json_path)));
     Ok(())
 }
 
-fn add_dir_to_zip<W: std::io::Write + std::io::Seek, T: zip::write::FileOptionExtension + Clone>(
-    zip: &mut zip::ZipWriter<W>,
-    base_path: &Path,
-    current_path: &Path,
-    options: &FileOptions<'_, T>,
-) -> zip::result::ZipResult<()> {
-    if current_path.is_dir() {
-        for entry in fs::read_dir(current_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            let name = path.strip_prefix(base_path).unwrap().to_string_lossy();
-
-            if path.is_dir() {
-                zip.add_directory(name, (*options).clone())?;
-                add_dir_to_zip(zip, base_path, &path, options)?;
-            } else {
-                zip.start_file(name, (*options).clone())?;
-                let mut f = fs::File::open(&path)?;
-                std::io::copy(&mut f, zip)?;
-            }
-        }
-    }
-    Ok(())
+/// 项目中的文件显示为相对路径，发布目录在项目之外时显示完整路径
+fn display_path(project: &Project, path: &Path) -> String {
+    path.strip_prefix(project.root()).unwrap_or(path).display().to_string()
 }
 
-fn clear_build_and_release_dirs() -> Result<(), Box<dyn std::error::Error>> {
-    let build_dir = Path::new(".ksmm/build");
-    let release_dir = Path::new(".ksmm/release");
-
-    // 清空 build 目录
-    if build_dir.exists() {
-        fs::remove_dir_all(build_dir)?;
-        println!("{} 清空 build 目录", "[+]".green());
+/// 写入构建时生成的文件
+///
+/// 用打包的 module.prop 替换构建目录中复制的版本，源文件可能没有写回或不包含变体的覆盖；
+/// 有 `bin/<abi>/` 时在 customize.sh 末尾加入按架构安装的代码。
+fn stage_generated_files(project: &Project, target: &Target) -> Result<(), KsmmError> {
+    target.prop.save(&project.build_dir().join("module.prop"))?;
+    if !target.binaries.is_empty() {
+        target.binaries.write_installer(&project.build_dir())?;
     }
-
-    // 清空 release 目录
-    if release_dir.exists() {
-        fs::remove_dir_all(release_dir)?;
-        println!("{} 清空 release 目录", "[+]".green());
-    }
-
     Ok(())
 }
 
-fn check_and_sign_release(module_info: &HashMap<String, String>) -> Result<(), Box<dyn std::error::Error>> {
-    println!("{} 开始检查签名", "🔍");
-    let key_dir = Path::new(".ksmm/key");
-
-    // 检查是否有.pem文件
-    let has_pem_files = if key_dir.exists() {
-        fs::read_dir(key_dir)?
-            .filter_map(|entry| entry.ok())
-            .any(|entry| {
-                entry.path().extension()
-                    .and_then(|ext| ext.to_str())
-                    .map(|ext| ext == "pem")
-                    .unwrap_or(false)
-            })
-    } else {
-        false
-    };
+/// 按架构安装的可执行文件目录，以逗号分隔
+fn abi_names(binaries: &AbiBinaries) -> String {
+    binaries.abis.keys().map(|abi| abi.name()).collect::<Vec<_>>().join(", ")
+}
 
-    if has_pem_files {
-        println!("{} 检测到PEM密钥文件", "🔑");
-    } else {
-        println!("{} 未检测到PEM密钥文件，跳过签名", "ℹ️");
-        return Ok(());
+/// 重新构建一次，并与刚生成的 zip 逐字节比较
+fn check_reproducible(project: &Project, target: &Target, zip_path: &Path) -> Result<(), KsmmError> {
+    println!("🔁 {}", tr!(build_reproducible_start).cyan());
+
+    project.clean_build_dir()?;
+    target.plan.execute(|_| {})?;
+    stage_generated_files(project, target)?;
+    let file_name = zip_path.file_name().unwrap_or_default().to_string_lossy();
+    let second = env::temp_dir().join(format!("ksmm-{}-{}", process::id(), file_name));
+    target.packager.package_to(&second)?;
+
+    let difference = build::compare_zips(zip_path, &second);
+    let _ = fs::remove_file(&second);
+    match difference? {
+        None => {
+            println!("✅ {}", tr!(build_reproducible_ok));
+            Ok(())
+        }
+        Some(entry) if entry.is_empty() => Err(KsmmError::Zip(tr!(build_not_reproducible, file_name))),
+        Some(entry) => Err(KsmmError::Zip(tr!(build_not_reproducible_entry, file_name, entry))),
     }
+}
 
-    // 获取模块信息用于签名
-    let id = module_info.get("id").unwrap_or(&"unknown".to_string()).clone();
-    let version_code = module_info.get("versionCode").unwrap_or(&"1".to_string()).clone();
-    let zip_filename = format!("{}-{}.zip", id, version_code);
-
-    let release_dir = Path::new(".ksmm/release");
-    let zip_path = release_dir.join(&zip_filename);
+/// 计划中的一个路径，`rule` 是决定它的规则，没有规则匹配时为 `None`
+struct PlannedPath<'a> {
+    is_dir: bool,
+    ignored: bool,
+    rule: Option<&'a IgnoreRule>,
+    /// 作为符号链接打包时的目标
+    symlink: Option<&'a Path>,
+    /// 来自变体覆盖目录时的源路径
+    overlay: Option<&'a Path>,
+    /// 由 cargo 构建的可执行文件
+    native: Option<&'a NativeBinary>,
+}
 
-    if !zip_path.exists() {
-        return Err("ZIP文件不存在，无法签名".into());
-    }
+/// 预演构建：只计算复制计划并输出 zip 中的目录树，选择了变体时每个变体输出一棵树
+fn dry_run(args: &BuildArgs) -> Result<(), KsmmError> {
+    println!("🔍 {}", tr!(build_dry_run_start).cyan());
 
-    // 构建签名命令
-    let zip_path_str = zip_path.to_string_lossy().to_string();
-    let signed_filename = format!("{}_signed.zip", zip_filename.trim_end_matches(".zip"));
-    let signed_path = release_dir.join(&signed_filename);
-
-    // 调用sign命令 (从系统调用)
-    let ksmm_path = std::env::current_exe()?.parent().unwrap().join("ksmm");
-    let sign_output = Command::new(ksmm_path)
-        .args(&["sign", &zip_path_str])
-        .output()?;
-
-
-    if sign_output.status.success() {
-        println!("{} 签名成功", "✅");
-        // 移动签名后的文件到release目录
-        let signed_source_name = format!("{}_signed.zip", zip_path_str.trim_end_matches(".zip"));
-        let signed_source = Path::new(&signed_source_name);
-        if signed_source.exists() {
-            fs::rename(&signed_source, &signed_path)?;
-            println!("{} 创建 .ksmm/release/{}", "[+]".green(), signed_filename);
-        } else {
-            println!("{} 签名完成，但未找到签名文件: {}", "⚠️", signed_source_name);
+    let project = Project::open_with_overrides(".", &args.overrides())?;
+    let native = project.native_build()?;
+    let native_binaries = native.as_ref().map(|native| native.binaries()).unwrap_or_default();
+    for variant in selected_variants(&project, args)? {
+        if let Some(variant) = &variant {
+            println!("🧩 {}", tr!(build_variant_start, variant.name).cyan());
+        }
+        let mut plan = project.plan_build_for(variant.as_ref())?;
+        plan.add_native(native_binaries, &project.build_dir());
+        let binaries = project.abi_binaries(&plan)?;
+        print_plan(&project, &plan, native_binaries, args.explain);
+        if !binaries.is_empty() {
+            println!("ℹ️ {}", tr!(build_dry_run_abi, abi_names(&binaries)));
         }
-    } else {
-        let stderr = String::from_utf8_lossy(&sign_output.stderr);
-        return Err(format!("签名失败: {}", stderr).into());
     }
-
     Ok(())
 }
 
-pub fn execute() {
-    println!("{} {}", "🔨", "构建模块...".cyan());
-
-    // 检查是否存在 module.prop 文件
-    let module_prop_path = Path::new("module.prop");
-    if !module_prop_path.exists() {
-        println!("{} 未找到 module.prop 文件，请确保在模块目录中运行此命令", "❌");
-        return;
-    }
-
-    // 前先清空build目录和release目录
-    if let Err(e) = clear_build_and_release_dirs() {
-        println!("{} 清空目录失败: {}", "❌", e);
-        return;
-    }
-
-    // 刷新 versionCode
-    if let Err(e) = refresh_version_code(&module_prop_path) {
-        println!("{} 刷新 versionCode 失败: {}", "❌", e);
-        return;
-    }
+/// 输出 zip 中的目录树，`explain` 时同时输出被忽略的路径与决定每个路径的规则
+fn print_plan(project: &Project, plan: &BuildPlan, native: &[NativeBinary], explain: bool) {
+    let paths = planned_paths(project, plan, native);
 
-    // 重新读取更新后的 module.prop
-    let module_prop_content = match fs::read_to_string(&module_prop_path) {
-        Ok(content) => content,
-        Err(e) => {
-            println!("{} 重新读取 module.prop 失败: {}", "❌", e);
-            return;
+    println!("📦 {}", tr!(build_dry_run_tree));
+    let (mut files, mut dirs) = (0, 0);
+    for (components, path) in &paths {
+        if path.ignored && !explain {
+            continue;
         }
-    };
-
-    let mut module_info = HashMap::new();
-    for line in module_prop_content.lines() {
-        if let Some((key, value)) = line.split_once('=') {
-            module_info.insert(key.to_string(), value.to_string());
+        if !path.ignored {
+            if path.is_dir { dirs += 1 } else { files += 1 }
         }
-    }
 
-    // 获取 git 短提交哈希
-    let short_commit = get_git_commit_hash();
-
-    // 创建 .ksmm 目录
-    let ksmm_dir = Path::new(".ksmm");
-    if let Err(e) = fs::create_dir_all(ksmm_dir) {
-        println!("{} 创建 .ksmm 目录失败: {}", "❌", e);
-        return;
-    }
-
-    // 创建 release 目录
-    let release_dir = Path::new(".ksmm/release");
-    if let Err(e) = fs::create_dir_all(release_dir) {
-        println!("{} 创建 release 目录失败: {}", "❌", e);
-        return;
-    }
+        let indent = "    ".repeat(components.len() - 1);
+        let name = match (components.last(), path.symlink) {
+            (Some(name), Some(target)) => format!("{} -> {}", name, target.display()),
+            (Some(name), None) if path.is_dir => format!("{}/", name),
+            (Some(name), None) => name.clone(),
+            (None, _) => continue,
+        };
+        if !explain {
+            println!("{}{}", indent, name);
+            continue;
+        }
 
-    // 生成 update.json
-    if let Err(e) = generate_update_json(&module_info, &short_commit, &release_dir) {
-        println!("{} 生成 update.json 失败: {}", "❌", e);
-        return;
+        let reason = match (path.native, path.overlay, path.rule) {
+            (Some(binary), _, _) => format!("({})", tr!(build_dry_run_native, binary.package, binary.target)),
+            (None, Some(src), _) => format!("({})", tr!(build_dry_run_overlay, display_path(project, src))),
+            (None, None, Some(rule)) => format!("({}, {})", rule, rule.location()),
+            (None, None, None) => format!("({})", tr!(build_dry_run_no_rule)),
+        };
+        if path.ignored {
+            println!("{}{} {} {}", indent, "[-]".red(), name.dimmed(), reason.dimmed());
+        } else {
+            println!("{}{} {} {}", indent, "[+]".green(), name, reason.dimmed());
+        }
     }
+    println!("ℹ️ {}", tr!(build_dry_run_summary, files, dirs));
 
-    // 复制文件到构建目录
-    let build_dir = Path::new(".ksmm/build");
-    if let Err(e) = copy_files_to_build(&build_dir) {
-        println!("{} 复制文件到构建目录失败: {}", "❌", e);
-        return;
+    if explain {
+        if plan.unmatched_rules.is_empty() {
+            println!("ℹ️ {}", tr!(build_dry_run_all_rules_matched));
+        } else {
+            println!("⚠️ {}", tr!(build_dry_run_unmatched_rules).yellow());
+            for rule in &plan.unmatched_rules {
+                println!("  {} {}", rule.location().yellow(), rule);
+            }
+        }
     }
+}
 
-    println!("{} 创建 .ksmm/release/update.json", "[+]".green());
-    println!("{} 模块构建完成!", "✅");
-
-    // 打包构建产物为ZIP
-    if let Err(e) = package_build_to_zip(&build_dir, &module_info) {
-        println!("{} 打包ZIP失败: {}", "❌", e);
-        return;
-    }
+/// 按构建目录中的路径排序计划中的所有路径，目录中的内容紧跟在目录之后
+fn planned_paths<'a>(
+    project: &Project,
+    plan: &'a BuildPlan,
+    native: &'a [NativeBinary],
+) -> BTreeMap<Vec<String>, PlannedPath<'a>> {
+    let components = |path: &Path, base: &Path| -> Option<Vec<String>> {
+        let rel = path.strip_prefix(base).ok()?;
+        let components: Vec<String> =
+            rel.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+        (!components.is_empty()).then_some(components)
+    };
 
-    // 检查并签名
-    if let Err(e) = check_and_sign_release(&module_info) {
-        println!("{} 签名过程失败: {}", "❌", e);
-        return;
+    let build_dir = project.build_dir();
+    let mut paths = BTreeMap::new();
+    for op in &plan.operations {
+        let Some(key) = components(&op.dst, &build_dir) else {
+            continue;
+        };
+        // 覆盖目录中的文件在项目中的路径与在构建目录中的路径不同
+        let from_overlay = components(&op.src, project.root()).as_ref() != Some(&key);
+        let path = paths.entry(key).or_insert(PlannedPath {
+            is_dir: false,
+            ignored: false,
+            rule: None,
+            symlink: None,
+            overlay: None,
+            native: None,
+        });
+        // cargo 构建的文件与为它们创建的目录，同样替换项目中同名的路径
+        if let Some(binary) = native.iter().find(|binary| binary.src == op.src) {
+            path.ignored = false;
+            path.rule = None;
+            path.overlay = None;
+            path.native = Some(binary);
+        } else if from_overlay {
+            // 覆盖目录中的内容不经过打包规则，被忽略的同名路径也会被打包
+            path.ignored = false;
+            path.rule = None;
+            path.overlay = Some(&op.src);
+        }
+        match &op.kind {
+            OperationKind::CreateDir => path.is_dir = true,
+            OperationKind::CopyFile => {}
+            OperationKind::Symlink(target) => path.symlink = Some(target),
+            OperationKind::Include(rule) => path.rule = Some(rule),
+            OperationKind::Ignore(rule) => {
+                path.is_dir = op.src.is_dir();
+                path.ignored = true;
+                path.rule = Some(rule);
+            }
+        }
     }
+    paths
 }
@@ -0,0 +1,80 @@
+use clap::Args;
+use owo_colors::OwoColorize;
+use ksmm::{Bump, KsmmError, Project, Version};
+use ksmm::changelog::{self, CHANGELOG_FILE};
+use ksmm::i18n::tr;
+
+/// `ksmm bump` 的命令行参数
+#[derive(Args, Debug)]
+pub struct BumpArgs {
+    /// major、minor、patch、prerelease 或明确的版本号
+    #[arg(value_name = "LEVEL|VERSION")]
+    target: String,
+
+    /// 从正式版本进入预发布版本时使用的标识
+    #[arg(long, value_name = "ID", default_value = "rc")]
+    pre_id: String,
+
+    /// 提交 module.prop 与 CHANGELOG.md 并创建 v* 标签，推送后触发发布工作流
+    #[arg(long)]
+    tag: bool,
+
+    /// 同时把 versionCode 重设为 CODE，即使小于现有的值，用于切换 version_code 策略
+    #[arg(long, value_name = "CODE", value_parser = clap::value_parser!(i32).range(1..))]
+    version_code: Option<i32>,
+}
+
+/// 按当前语言替换 `ksmm bump` 参数的帮助文本
+pub fn localize(cmd: clap::Command) -> clap::Command {
+    cmd.about(tr!(help_bump))
+        .mut_arg("target", |a| a.help(tr!(help_bump_target)))
+        .mut_arg("pre_id", |a| a.help(tr!(help_bump_pre_id)))
+        .mut_arg("tag", |a| a.help(tr!(help_bump_tag)))
+        .mut_arg("version_code", |a| a.help(tr!(help_bump_version_code)))
+}
+
+pub fn execute(args: BumpArgs) -> Result<(), KsmmError> {
+    let project = Project::open(".")?;
+    let current = project.current_version()?;
+
+    let next = match args.target.as_str() {
+        "major" => current.bump(Bump::Major, &args.pre_id),
+        "minor" => current.bump(Bump::Minor, &args.pre_id),
+        "patch" => current.bump(Bump::Patch, &args.pre_id),
+        "prerelease" => current.bump(Bump::Prerelease, &args.pre_id),
+        explicit => {
+            let version = Version::parse(explicit).ok_or_else(|| KsmmError::Input(tr!(bump_bad_target, explicit)))?;
+            // 与 module.prop 中原有的写法保持一致
+            Version { prefixed: current.prefixed, ..version }
+        }
+    };
+
+    // 修改文件之前确认标签可用
+    if args.tag && project.git_tag_exists(&next.tag())? {
+        return Err(KsmmError::Git(tr!(bump_tag_exists, next.tag())));
+    }
+
+    project.set_version(&next)?;
+    println!("🔖 {}", tr!(bump_version, current, next.to_string().green()));
+
+    if let Some(code) = args.version_code {
+        let previous = project.reset_version_code(code.into())?;
+        println!("{} {}", "[~]".yellow(), tr!(bump_version_code_reset, previous, code));
+    }
+
+    // 新段落中写入上一个版本以来的提交
+    let changes = project.release_changes(&next)?;
+    if project.write_changelog_section(&next, &changelog::render(&changes.entries))? {
+        println!("{} {}", "[+]".green(), tr!(bump_changelog_added, CHANGELOG_FILE, next.tag()));
+    } else {
+        println!("{} {}", "[~]".yellow(), tr!(bump_changelog_exists, CHANGELOG_FILE, next.tag()));
+    }
+
+    if args.tag {
+        let tag = project.tag_release(&next)?;
+        println!("{} {}", "[+]".green(), tr!(bump_tagged, tag));
+        println!("{} {}", "💡".blue(), tr!(bump_push_hint));
+    }
+
+    Ok(())
+}
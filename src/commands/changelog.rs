@@ -0,0 +1,55 @@
+use clap::Args;
+use owo_colors::OwoColorize;
+use ksmm::{KsmmError, Project};
+use ksmm::changelog::{self, CHANGELOG_FILE};
+use ksmm::i18n::tr;
+
+/// `ksmm changelog` 的命令行参数
+#[derive(Args, Debug)]
+pub struct ChangelogArgs {
+    /// 只向标准输出打印生成的内容，不修改 CHANGELOG.md，可用作发布说明
+    #[arg(long)]
+    stdout: bool,
+}
+
+/// 按当前语言替换 `ksmm changelog` 参数的帮助文本
+pub fn localize(cmd: clap::Command) -> clap::Command {
+    cmd.about(tr!(help_changelog))
+        .mut_arg("stdout", |a| a.help(tr!(help_changelog_stdout)))
+}
+
+pub fn execute(args: ChangelogArgs) -> Result<(), KsmmError> {
+    let project = Project::open(".")?;
+    let version = project.current_version()?;
+    let changes = project.release_changes(&version)?;
+    let body = changelog::render(&changes.entries);
+
+    // 标准输出中只有 Markdown，便于直接重定向为发布说明
+    if args.stdout {
+        print!("{}", body);
+        return Ok(());
+    }
+
+    println!("📝 {}", tr!(changelog_start).cyan());
+    let range = match &changes.since {
+        Some(since) => format!("{}..{}", since, changes.until),
+        None => changes.until.clone(),
+    };
+    println!("{} {}", "[+]".green(), tr!(changelog_range, range.yellow(), changes.entries.len()));
+
+    if changes.entries.is_empty() {
+        println!("{} {}", "[~]".yellow(), tr!(changelog_nothing));
+        return Ok(());
+    }
+
+    if project.write_changelog_section(&version, &body)? {
+        println!("{} {}", "[+]".green(), tr!(changelog_written, CHANGELOG_FILE, version.tag()));
+    } else {
+        println!("{} {}", "[~]".yellow(), tr!(changelog_kept, CHANGELOG_FILE, version.tag()));
+        // 当前版本还没有发布时，这些提交属于下一个版本
+        if changes.until == "HEAD" {
+            println!("{} {}", "💡".blue(), tr!(changelog_bump_hint));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use clap::Args;
+use owo_colors::OwoColorize;
+use std::path::PathBuf;
+use ksmm::{KsmmError, Project, Severity};
+use ksmm::i18n::tr;
+
+/// `ksmm check` 的命令行参数
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    /// 模块目录
+    #[arg(default_value = ".")]
+    path: PathBuf,
+}
+
+/// 按当前语言替换 `ksmm check` 参数的帮助文本
+pub fn localize(cmd: clap::Command) -> clap::Command {
+    cmd.about(tr!(help_check))
+        .mut_arg("path", |a| a.help(tr!(help_check_path)))
+}
+
+pub fn execute(args: CheckArgs) -> Result<(), KsmmError> {
+    println!("🔎 {}", tr!(check_start).cyan());
+
+    let project = Project::open(&args.path)?;
+    let diagnostics = project.check()?;
+
+    for diagnostic in &diagnostics {
+        match diagnostic.severity {
+            Severity::Error => println!(
+                "❌ {}: {}: {}",
+                diagnostic.location().yellow(),
+                tr!(check_error).red(),
+                diagnostic.message
+            ),
+            Severity::Warning => println!(
+                "⚠️ {}: {}: {}",
+                diagnostic.location().yellow(),
+                tr!(check_warning).yellow(),
+                diagnostic.message
+            ),
+        }
+    }
+
+    let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+    let warnings = diagnostics.len() - errors;
+    if errors > 0 {
+        println!();
+        return Err(KsmmError::Check(tr!(check_failed, errors, warnings)));
+    }
+
+    println!("✅ {}", tr!(check_passed, warnings).cyan());
+    Ok(())
+}
@@ -0,0 +1,365 @@
+use clap::Args;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::commands::init::update_gitignore;
+use crate::template::{Template, PROJECT_LAYER};
+use ksmm::{KsmmError, ModuleProp};
+use ksmm::i18n::{self, tr, Message};
+
+/// `ksmm convert` 的命令行参数
+#[derive(Args, Debug)]
+pub struct ConvertArgs {
+    /// 模块目录
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// 只报告需要修改的内容，不修改任何文件
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// 按当前语言替换 `ksmm convert` 参数的帮助文本
+pub fn localize(cmd: clap::Command) -> clap::Command {
+    cmd.about(tr!(help_convert))
+        .mut_arg("path", |a| a.help(tr!(help_convert_path)))
+        .mut_arg("dry_run", |a| a.help(tr!(help_convert_dry_run)))
+}
+
+/// 被移除的文件备份到这里，.ksmm 目录不会被打包
+const BACKUP_DIR: &str = ".ksmm/convert-backup";
+
+/// Magisk 旧模板中由 `common/` 目录提供、KernelSU 要求放在模块根目录的文件，以及控制它们的开关
+const COMMON_FILES: &[(&str, Option<&str>)] = &[
+    ("post-fs-data.sh", Some("POSTFSDATA")),
+    ("service.sh", Some("LATESTARTSERVICE")),
+    ("system.prop", Some("PROPFILE")),
+    ("sepolicy.rule", None),
+];
+
+/// 脚本中只有 Magisk 才提供的接口
+const MAGISK_APIS: &[(&str, Message)] = &[
+    (r"\bmagisk\s+--", |c| c.convert_api_magisk_cmd),
+    (r"/sbin/\.magisk|\$MAGISKTMP\b", |c| c.convert_api_magisk_tmp),
+    (r"/data/adb/magisk\b", |c| c.convert_api_magisk_data),
+    (r"\bmagiskpolicy\b", |c| c.convert_api_magiskpolicy),
+    (r"\$\{?MAGISK_VER(_CODE)?\b", |c| c.convert_api_magisk_ver),
+    (r"util_functions\.sh", |c| c.convert_api_util_functions),
+    (r"\$\{?MOUNTPATH\b|\bmount_magisk_img\b|\bimageless_magisk\b", |c| c.convert_api_mountpath),
+    (r"(?i)\bzygisk\b", |c| c.convert_api_zygisk),
+    (r"(?i)\briru\b", |c| c.convert_api_riru),
+];
+
+/// module.prop 中只对 Magisk 有意义的字段
+const MAGISK_PROP_KEYS: &[&str] = &["minMagisk", "template"];
+
+/// 转换计划中的一步
+#[derive(Debug)]
+enum Step {
+    /// 备份后删除
+    Remove(String),
+    /// 移动到模块根目录
+    Move { from: String, to: String },
+    /// 写入新文件
+    Create { path: String, content: String },
+}
+
+/// 扫描到的问题
+struct Finding {
+    file: String,
+    line: usize,
+    message: String,
+}
+
+/// Magisk 旧模板安装脚本中的开关，例如 `SKIPMOUNT=false`
+fn read_flags(content: &str) -> BTreeMap<String, bool> {
+    let flag_regex = Regex::new(r"^\s*([A-Z_]+)\s*=\s*(true|false)\s*$").unwrap();
+    content.lines()
+        .filter_map(|line| flag_regex.captures(line))
+        .map(|caps| (caps[1].to_string(), &caps[2] == "true"))
+        .collect()
+}
+
+/// 由 config.sh / install.sh 生成 customize.sh
+///
+/// 旧模板的安装器会依次调用其中定义的函数，KernelSU 只会 source customize.sh，因此在末尾补上这些调用。
+fn customize_from_installer(name: &str, content: &str) -> String {
+    let mut customize = String::from("#!/system/bin/sh\n");
+    customize.push_str(&format!("# 由 ksmm convert 从 {} 转换而来\n\n", name));
+
+    for line in content.lines() {
+        // 旧脚本的 shebang 与 util_functions 引用由 KernelSU 安装器提供
+        if line.starts_with("#!") || line.contains("util_functions.sh") {
+            continue;
+        }
+        customize.push_str(line);
+        customize.push('\n');
+    }
+
+    let calls: Vec<&str> = ["print_modname", "on_install", "set_permissions"]
+        .into_iter()
+        .filter(|func| {
+            Regex::new(&format!(r"(?m)^\s*(function\s+)?{}\s*\(\)", func)).unwrap().is_match(content)
+        })
+        .collect();
+    if !calls.is_empty() {
+        customize.push_str(&format!("\n# 以下为原 {} 的安装流程\n", name));
+        for func in calls {
+            customize.push_str(func);
+            customize.push('\n');
+        }
+    }
+
+    customize
+}
+
+fn plan_conversion(base_path: &Path, notes: &mut Vec<String>) -> io::Result<Vec<Step>> {
+    let mut steps = Vec::new();
+
+    // META-INF 安装器
+    let meta_inf = "META-INF/com/google/android";
+    for file in ["update-binary", "updater-script"] {
+        let path = format!("{}/{}", meta_inf, file);
+        if base_path.join(&path).exists() {
+            steps.push(Step::Remove(path));
+        }
+    }
+    if base_path.join(meta_inf).join("update-binary").exists() {
+        let update_binary = fs::read_to_string(base_path.join(meta_inf).join("update-binary")).unwrap_or_default();
+        if !update_binary.contains("util_functions.sh") && !update_binary.contains("install_module") {
+            notes.push(tr!(convert_custom_update_binary).to_string());
+        }
+    }
+
+    // config.sh / install.sh 旧模板
+    let mut flags = BTreeMap::new();
+    for installer in ["config.sh", "install.sh"] {
+        let installer_path = base_path.join(installer);
+        if !installer_path.is_file() {
+            continue;
+        }
+
+        let content = fs::read_to_string(&installer_path)?;
+        flags.extend(read_flags(&content));
+
+        if base_path.join("customize.sh").exists() || steps.iter().any(|s| matches!(s, Step::Create { path, .. } if path == "customize.sh")) {
+            notes.push(tr!(convert_merge_installer, installer));
+            continue;
+        }
+
+        steps.push(Step::Create { path: "customize.sh".to_string(), content: customize_from_installer(installer, &content) });
+        steps.push(Step::Remove(installer.to_string()));
+    }
+
+    // 重复转换时，开关从上次备份的安装脚本中读取
+    for installer in ["config.sh", "install.sh"] {
+        if let Ok(content) = fs::read_to_string(base_path.join(BACKUP_DIR).join(installer)) {
+            for (flag, value) in read_flags(&content) {
+                flags.entry(flag).or_insert(value);
+            }
+        }
+    }
+
+    if flags.get("SKIPMOUNT") == Some(&true) && !base_path.join("skip_mount").exists() {
+        steps.push(Step::Create { path: "skip_mount".to_string(), content: String::new() });
+    }
+
+    // common/ 目录中的脚本与配置
+    for (file, flag) in COMMON_FILES {
+        let from = format!("common/{}", file);
+        if !base_path.join(&from).is_file() {
+            continue;
+        }
+
+        if let Some(flag) = flag
+            && flags.get(*flag) == Some(&false)
+        {
+            notes.push(tr!(convert_flag_disabled, from, flag));
+            continue;
+        }
+        if base_path.join(file).exists() {
+            notes.push(tr!(convert_merge_common, file, from));
+            continue;
+        }
+
+        steps.push(Step::Move { from, to: file.to_string() });
+    }
+
+    Ok(steps)
+}
+
+fn apply_step(base_path: &Path, step: &Step) -> io::Result<()> {
+    match step {
+        Step::Remove(path) => {
+            let src = base_path.join(path);
+            let backup = base_path.join(BACKUP_DIR).join(path);
+            if let Some(parent) = backup.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&src, &backup)?;
+            println!("{} {}", "[-]".red(), tr!(convert_removed, path, format!("{}/{}", BACKUP_DIR, path)));
+        }
+        Step::Move { from, to } => {
+            fs::rename(base_path.join(from), base_path.join(to))?;
+            println!("{} {}", "[~]".yellow(), tr!(convert_moved, from, to));
+        }
+        Step::Create { path, content } => {
+            fs::write(base_path.join(path), content)?;
+            println!("{} {}", "[+]".green(), tr!(init_created_file, path));
+        }
+    }
+    Ok(())
+}
+
+fn describe_step(step: &Step) {
+    match step {
+        Step::Remove(path) => println!("{} {}", "[-]".red(), tr!(convert_will_remove, path)),
+        Step::Move { from, to } => println!("{} {}", "[~]".yellow(), tr!(convert_will_move, from, to)),
+        Step::Create { path, .. } => println!("{} {}", "[+]".green(), tr!(convert_will_create, path)),
+    }
+}
+
+/// 删除转换后留下的空目录
+fn remove_empty_dirs(base_path: &Path) {
+    for dir in ["META-INF/com/google/android", "META-INF/com/google", "META-INF/com", "META-INF", "common"] {
+        let path = base_path.join(dir);
+        if path.read_dir().is_ok_and(|mut entries| entries.next().is_none()) {
+            let _ = fs::remove_dir(&path);
+        }
+    }
+}
+
+/// 扫描脚本中的 Magisk 专用接口
+fn scan_scripts(base_path: &Path, steps: &[Step]) -> io::Result<Vec<Finding>> {
+    let patterns: Vec<(Regex, &str)> = MAGISK_APIS.iter()
+        .map(|(pattern, message)| (Regex::new(pattern).unwrap(), message(i18n::catalog())))
+        .collect();
+
+    // 转换后仍然存在的脚本：根目录和 common/ 中的 .sh，以及即将生成的 customize.sh
+    let mut scripts: Vec<(String, String)> = Vec::new();
+    for dir in ["", "common"] {
+        let Ok(entries) = fs::read_dir(base_path.join(dir)) else {
+            continue;
+        };
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let rel_path = if dir.is_empty() { name.clone() } else { format!("{}/{}", dir, name) };
+            let removed = steps.iter().any(|s| matches!(s, Step::Remove(p) if *p == rel_path));
+            if !name.ends_with(".sh") || !entry.path().is_file() || removed {
+                continue;
+            }
+
+            // 按转换后的位置报告
+            let rel_path = steps.iter()
+                .find_map(|s| match s {
+                    Step::Move { from, to } if *from == rel_path => Some(to.clone()),
+                    _ => None,
+                })
+                .unwrap_or(rel_path);
+            scripts.push((rel_path, fs::read_to_string(entry.path()).unwrap_or_default()));
+        }
+    }
+    for step in steps {
+        if let Step::Create { path, content } = step
+            && path.ends_with(".sh")
+        {
+            scripts.retain(|(p, _)| p != path);
+            scripts.push((path.clone(), content.clone()));
+        }
+    }
+    scripts.sort();
+
+    let mut findings = Vec::new();
+    for (file, content) in &scripts {
+        for (index, line) in content.lines().enumerate() {
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+            for (regex, message) in &patterns {
+                if regex.is_match(line) {
+                    findings.push(Finding { file: file.clone(), line: index + 1, message: message.to_string() });
+                }
+            }
+        }
+    }
+
+    // module.prop 中的 Magisk 字段
+    if let Ok(prop) = ModuleProp::load(&base_path.join("module.prop")) {
+        for (line, key, _) in prop.entries() {
+            if MAGISK_PROP_KEYS.contains(&key) {
+                findings.push(Finding {
+                    file: "module.prop".to_string(),
+                    line,
+                    message: tr!(convert_magisk_prop, key),
+                });
+            }
+        }
+    }
+
+    Ok(findings)
+}
+
+pub fn execute(args: ConvertArgs) -> Result<(), KsmmError> {
+    println!("🔄 {}", tr!(convert_start).cyan());
+
+    let base_path = args.path.as_path();
+    if !base_path.join("module.prop").exists() {
+        return Err(KsmmError::ModulePropMissing);
+    }
+
+    let mut notes = Vec::new();
+    let steps = plan_conversion(base_path, &mut notes)
+        .map_err(|e| KsmmError::io(tr!(convert_analyze_failed, e), e))?;
+
+    if steps.is_empty() {
+        println!("ℹ️ {}", tr!(convert_nothing));
+    } else {
+        println!("🔍 {}", tr!(convert_detected));
+    }
+
+    let findings = scan_scripts(base_path, &steps)
+        .map_err(|e| KsmmError::io(tr!(convert_scan_failed, e), e))?;
+
+    if args.dry_run {
+        for step in &steps {
+            describe_step(step);
+        }
+    } else {
+        for step in &steps {
+            apply_step(base_path, step).map_err(|e| KsmmError::io(tr!(convert_failed, e), e))?;
+        }
+        remove_empty_dirs(base_path);
+
+        // 补齐 ksmm 项目文件，使 ksmm build 可以直接打包
+        let mut template = Template::empty("convert");
+        template.add_layer(PROJECT_LAYER);
+        template.render_into(base_path, &BTreeMap::new())
+            .map_err(|e| KsmmError::io(tr!(init_project_files_failed, e), e))?;
+        update_gitignore(base_path);
+    }
+
+    for note in &notes {
+        println!("{} {}", "💡".blue(), note);
+    }
+
+    if !findings.is_empty() {
+        println!();
+        println!("⚠️ {}", tr!(convert_findings, findings.len()));
+        for finding in &findings {
+            println!("  {}:{}: {}", finding.file.yellow(), finding.line, finding.message);
+        }
+    }
+
+    println!();
+    if args.dry_run {
+        println!("ℹ️ {}", tr!(convert_dry_run));
+    } else {
+        println!("✅ {}", tr!(convert_done).cyan());
+        println!("📋 {}", tr!(convert_next, "'ksmm build'".green()));
+    }
+    Ok(())
+}
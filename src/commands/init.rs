@@ -1,15 +1,57 @@
-use dialoguer::{Input, Confirm};
+use clap::Args;
+use dialoguer::{Input, Confirm, Select};
 use owo_colors::OwoColorize;
+use serde::Deserialize;
 use std::fs;
-use std::path::Path;
+use std::io::{self, IsTerminal};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
-use chrono::{Datelike, Timelike, Utc};
 use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Read;
+use zip::ZipArchive;
+use crate::template::{Template, Manifest, Condition, PromptKind, BUILTIN_TEMPLATES, ACTION_LAYER, WEBUI_LAYER, PROJECT_LAYER};
+use ksmm::{Config, KsmmError, Overrides};
+use ksmm::{ModuleProp, Version};
+use ksmm::build;
+use ksmm::module_prop::{is_valid_id, timestamp_version_code};
+use ksmm::i18n::{self, tr};
+
+/// 初始化时收集到的模块信息
+struct ModuleSpec {
+    id: String,
+    name: String,
+    version: String,
+    version_code: i32,
+    author: String,
+    description: String,
+    update_json: String,
+}
+
+impl ModuleSpec {
+    /// 模板中可用的占位符，`tag` 与 `ksmm bump` 写入 CHANGELOG.md 的版本标题相同，总是带有 `v` 前缀
+    fn template_vars(&self) -> BTreeMap<String, String> {
+        let tag = match Version::parse(&self.version) {
+            Some(version) => version.tag(),
+            None => format!("v{}", self.version.trim_start_matches('v')),
+        };
+        BTreeMap::from([
+            ("id".to_string(), self.id.clone()),
+            ("name".to_string(), self.name.clone()),
+            ("version".to_string(), self.version.clone()),
+            ("tag".to_string(), tag),
+            ("versionCode".to_string(), self.version_code.to_string()),
+            ("author".to_string(), self.author.clone()),
+            ("description".to_string(), self.description.clone()),
+            ("updateJson".to_string(), self.update_json.clone()),
+        ])
+    }
+}
 
 fn get_git_info() -> (String, Option<String>, Option<String>, Option<String>, Option<String>) {
     // 获取分支信息
     let branch_output = Command::new("git")
-        .args(&["branch", "--show-current"])
+        .args(["branch", "--show-current"])
         .output();
 
     let branch = if let Ok(output) = branch_output {
@@ -24,52 +66,52 @@ fn get_git_info() -> (String, Option<String>, Option<String>, Option<String>, Op
 
     // 获取远程仓库URL
     let remote_output = Command::new("git")
-        .args(&["remote", "get-url", "origin"])
+        .args(["remote", "get-url", "origin"])
         .output();
 
     let mut remote_url = None;
     let mut update_json = String::new();
     let mut username = None;
 
-    if let Ok(output) = remote_output {
-        if output.status.success() {
-            let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            remote_url = Some(url.clone());
-
-            // 解析GitHub URL
-            let github_regex = Regex::new(r"github\.com[\/:]([^\/]+)\/([^\/\.]+)").unwrap();
-
-            if let Some(captures) = github_regex.captures(&url) {
-                if let (Some(user), Some(repo)) = (captures.get(1), captures.get(2)) {
-                    let user = user.as_str();
-                    let repo = repo.as_str().trim_end_matches(".git");
-                    update_json = format!("https://github.com/{}/{}/releases/latest/download/update.json", user, repo);
-                    username = Some(user.to_string());
-                }
-            }
+    if let Ok(output) = remote_output
+        && output.status.success()
+    {
+        let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        remote_url = Some(url.clone());
+
+        // 解析GitHub URL
+        let github_regex = Regex::new(r"github\.com[\/:]([^\/]+)\/([^\/\.]+)").unwrap();
+
+        if let Some(captures) = github_regex.captures(&url)
+            && let (Some(user), Some(repo)) = (captures.get(1), captures.get(2))
+        {
+            let user = user.as_str();
+            let repo = repo.as_str().trim_end_matches(".git");
+            update_json = format!("https://github.com/{}/{}/releases/latest/download/update.json", user, repo);
+            username = Some(user.to_string());
         }
     }
 
     // 如果无法从远程URL获取，尝试获取git用户名
     if username.is_none() {
         let user_output = Command::new("git")
-            .args(&["config", "user.name"])
+            .args(["config", "user.name"])
             .output();
 
-        if let Ok(output) = user_output {
-            if output.status.success() {
-                let user = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                if !user.is_empty() {
-                    update_json = format!("https://github.com/{}/ksmm/releases/latest/download/update.json", user);
-                    username = Some(user);
-                }
+        if let Ok(output) = user_output
+            && output.status.success()
+        {
+            let user = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !user.is_empty() {
+                update_json = format!("https://github.com/{}/ksmm/releases/latest/download/update.json", user);
+                username = Some(user);
             }
         }
     }
 
     // 获取工作目录状态
     let status_output = Command::new("git")
-        .args(&["status", "--porcelain"])
+        .args(["status", "--porcelain"])
         .output();
 
     let is_clean = if let Ok(output) = status_output {
@@ -78,7 +120,7 @@ fn get_git_info() -> (String, Option<String>, Option<String>, Option<String>, Op
         false
     };
 
-    let status = if is_clean { "工作目录清洁" } else { "工作目录有变更" };
+    let status = if is_clean { tr!(git_status_clean) } else { tr!(git_status_dirty) };
 
     // 如果都获取不到，使用默认的ksmm
     if update_json.is_empty() {
@@ -88,543 +130,773 @@ fn get_git_info() -> (String, Option<String>, Option<String>, Option<String>, Op
     (update_json, username, branch, remote_url, Some(status.to_string()))
 }
 
-fn create_system_directory(base_path: &Path) {
-    let system_path = base_path.join("system");
-    if system_path.exists() {
-        println!("{}", format!("  [!] system 目录已存在，跳过创建").dimmed());
+/// 忽略 .ksmm/ 中的构建产物与密钥，但保留项目配置
+const GITIGNORE_LINES: &[&str] = &[".ksmm/*", "!.ksmm/build.conf", "!.ksmm/ksmm.toml"];
+
+pub fn update_gitignore(base_path: &Path) {
+    // 只在项目目录（base_path）内查找或创建 .gitignore
+    let gitignore_path = base_path.join(".gitignore");
+
+    // 读取或创建 .gitignore
+    let content = if gitignore_path.exists() {
+        match fs::read_to_string(&gitignore_path) {
+            Ok(c) => c,
+            Err(e) => {
+                println!("{}", format!("  [!] {}", tr!(gitignore_read_failed, e)).dimmed());
+                return;
+            }
+        }
     } else {
-        fs::create_dir_all(&system_path).expect("无法创建 system 目录");
-        println!("{} 创建 system 目录", "[+]".green());
+        // 如果不存在，创建一个空的内容
+        String::new()
+    };
 
-        // 创建 system/etc 目录
-        let etc_path = system_path.join("etc");
-        fs::create_dir_all(&etc_path).expect("无法创建 system/etc 目录");
+    let is_empty = content.is_empty();
 
-        // 创建示例配置文件
-        let example_conf_path = etc_path.join("example.conf");
-        let example_conf_content = "# 这是一个示例配置文件\n# 将此文件放置在system目录中，它会被挂载到 /system/etc/example.conf\n";
-        fs::write(&example_conf_path, example_conf_content).expect("无法写入 example.conf");
-        println!("{} 创建 system/etc/example.conf", "[+]".green());
-    }
-}
+    // 与 git 相同，目录本身被忽略后无法重新包括其中的文件，因此忽略的是 .ksmm/ 中的内容
+    let has_legacy_ksmm = content.lines().any(|line| line.trim() == ".ksmm/");
+    let up_to_date = GITIGNORE_LINES.iter().all(|expected| content.lines().any(|line| line.trim() == *expected));
 
-fn create_module_prop(base_path: &Path, id: &str, name: &str, version: &str, version_code: i32, author: &str, description: &str, update_json: &str) {
-    let module_prop_path = base_path.join("module.prop");
-    if module_prop_path.exists() {
-        println!("{}", format!("  [!] module.prop 文件已存在，跳过创建").dimmed());
+    if up_to_date && !has_legacy_ksmm {
+        println!("{}", format!("  [!] {}", tr!(gitignore_up_to_date)).dimmed());
     } else {
-        let module_prop_content = format!(
-            "id={}\nname={}\nversion={}\nversionCode={}\nauthor={}\ndescription={}\nupdateJson={}\n",
-            id, name, version, version_code, author, description, update_json
-        );
-        fs::write(&module_prop_path, module_prop_content).expect("无法写入 module.prop");
-        println!("{} 创建 module.prop", "[+]".green());
+        // 旧版本写入的 .ksmm/ 会让其后的 ! 规则失效，替换为 .ksmm/*
+        let mut new_content = content
+            .lines()
+            .filter(|line| line.trim() != ".ksmm/" && !GITIGNORE_LINES.contains(&line.trim()))
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+        for line in GITIGNORE_LINES {
+            new_content.push_str(line);
+            new_content.push('\n');
+        }
+
+        match fs::write(&gitignore_path, new_content) {
+            Ok(_) => {
+                if is_empty {
+                    println!("{} {}", "[+]".green(), tr!(init_created_file, ".gitignore"));
+                } else {
+                    println!("{} {}", "[+]".green(), tr!(gitignore_updated));
+                }
+            }
+            Err(e) => {
+                println!("{}", format!("  [!] {}", tr!(gitignore_write_failed, e)).dimmed());
+            }
+        }
     }
 }
 
-fn create_script_files(base_path: &Path) {
-    let customize_content = r#"#!/system/bin/sh
-# KernelSU 模块自定义安装脚本
-
-# 检查设备信息
-ui_print "- 设备架构: $ARCH"
-ui_print "- Android API: $API"
-ui_print "- KernelSU 版本: $KSU_VER"
-
-# 根据设备架构进行不同的处理
-case $ARCH in
-    arm64)
-        ui_print "- 64位ARM设备"
-        ;;
-    arm)
-        ui_print "- 32位ARM设备"
-        ;;
-    x64)
-        ui_print "- x86_64设备"
-        ;;
-    x86)
-        ui_print "- x86设备"
-        ;;
-esac
-
-# 根据Android版本进行处理
-# 示例shellcheck 自动修复 $API -> "$API"
-if [ $API -lt 29 ]; then
-    ui_print "- Android 10以下版本"
-else
-    ui_print "- Android 10及以上版本"
-fi
-
-# 设置权限（如果需要）
-# set_perm_recursive $MODPATH/system/bin 0 0 0755 0755
-# set_perm $MODPATH/system/etc/example.conf 0 0 0644
-
-# 示例：删除系统文件（取消注释以使用）
-# REMOVE="
-# /system/app/SomeSystemApp
-# /system/etc/some_config_file
-# "
-
-# 示例：替换系统目录（取消注释以使用）
-# REPLACE="
-# /system/app/SomeSystemApp
-# "
-
-ui_print "- 模块安装完成"
-"#;
-
-    let scripts = [
-        ("post-fs-data.sh", "#!/system/bin/sh\n# 在文件系统挂载后执行\n"),
-        ("service.sh", "#!/system/bin/sh\n# 服务脚本\n"),
-        ("customize.sh", customize_content),
-    ];
-
-    for (filename, content) in &scripts {
-        let file_path = base_path.join(filename);
-        if file_path.exists() {
-            println!("{}", format!("  [!] {} 文件已存在，跳过创建", filename).dimmed());
-        } else {
-            fs::write(&file_path, content).expect(&format!("无法写入 {}", filename));
-            println!("{} 创建 {}", "[+]".green(), filename);
+/// `ksmm init` 的命令行参数
+///
+/// 未通过参数或答案文件给出的值，在有终端时仍会交互式询问。
+#[derive(Args, Debug, Default)]
+pub struct InitArgs {
+    /// 创建地址
+    #[arg(long)]
+    path: Option<String>,
+
+    /// 模块 ID
+    #[arg(long)]
+    id: Option<String>,
+
+    /// 模块名称 (默认与 ID 相同)
+    #[arg(long)]
+    name: Option<String>,
+
+    /// 模块作者 (默认使用 git 用户名)
+    #[arg(long)]
+    author: Option<String>,
+
+    /// 模块描述
+    #[arg(long)]
+    description: Option<String>,
+
+    /// 模块版本 (默认 0.1.0)
+    #[arg(long)]
+    version: Option<String>,
+
+    /// updateJson 地址 (默认根据 git 远程仓库生成)
+    #[arg(long)]
+    update_json: Option<String>,
+
+    /// 创建执行按钮脚本 action.sh
+    #[arg(long, overrides_with = "no_action")]
+    action: bool,
+
+    /// 不创建执行按钮脚本
+    #[arg(long, overrides_with = "action")]
+    no_action: bool,
+
+    /// 创建 WebUI
+    #[arg(long, overrides_with = "no_webui")]
+    webui: bool,
+
+    /// 不创建 WebUI
+    #[arg(long, overrides_with = "webui")]
+    no_webui: bool,
+
+    /// 不进行交互，未指定的值全部使用默认值
+    #[arg(short = 'y', long)]
+    yes: bool,
+
+    /// 使用的模板：内置模板名称或本地模板目录
+    #[arg(short = 't', long, value_name = "NAME|DIR")]
+    template: Option<String>,
+
+    /// 列出内置模板
+    #[arg(long)]
+    list_templates: bool,
+
+    /// 从已发布的模块 zip 导入为 ksmm 项目
+    #[arg(long, value_name = "ZIP", conflicts_with_all = ["template", "list_templates"])]
+    from_zip: Option<PathBuf>,
+
+    /// 回答模板清单中的问题，可重复使用
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    set: Vec<String>,
+
+    /// 从 TOML 答案文件读取上述选项
+    #[arg(long, value_name = "FILE")]
+    answers: Option<PathBuf>,
+}
+
+/// 按当前语言替换 `ksmm init` 参数的帮助文本
+pub fn localize(cmd: clap::Command) -> clap::Command {
+    cmd.about(tr!(help_init))
+        .mut_arg("path", |a| a.help(tr!(help_init_path)))
+        .mut_arg("id", |a| a.help(tr!(help_init_id)))
+        .mut_arg("name", |a| a.help(tr!(help_init_name)))
+        .mut_arg("author", |a| a.help(tr!(help_init_author)))
+        .mut_arg("description", |a| a.help(tr!(help_init_description)))
+        .mut_arg("version", |a| a.help(tr!(help_init_version)))
+        .mut_arg("update_json", |a| a.help(tr!(help_init_update_json)))
+        .mut_arg("action", |a| a.help(tr!(help_init_action)))
+        .mut_arg("no_action", |a| a.help(tr!(help_init_no_action)))
+        .mut_arg("webui", |a| a.help(tr!(help_init_webui)))
+        .mut_arg("no_webui", |a| a.help(tr!(help_init_no_webui)))
+        .mut_arg("yes", |a| a.help(tr!(help_init_yes)))
+        .mut_arg("template", |a| a.help(tr!(help_init_template)))
+        .mut_arg("list_templates", |a| a.help(tr!(help_init_list_templates)))
+        .mut_arg("from_zip", |a| a.help(tr!(help_init_from_zip)))
+        .mut_arg("set", |a| a.help(tr!(help_init_set)))
+        .mut_arg("answers", |a| a.help(tr!(help_init_answers)))
+}
+
+/// 答案文件内容，字段与命令行参数一一对应
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Answers {
+    path: Option<String>,
+    id: Option<String>,
+    name: Option<String>,
+    author: Option<String>,
+    description: Option<String>,
+    version: Option<String>,
+    #[serde(rename = "updateJson", alias = "update_json")]
+    update_json: Option<String>,
+    template: Option<String>,
+    action: Option<bool>,
+    webui: Option<bool>,
+    /// 模板清单中问题的回答
+    #[serde(default)]
+    vars: BTreeMap<String, toml::Value>,
+}
+
+impl Answers {
+    fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// 命令行参数优先于答案文件
+    fn merge_args(self, args: &InitArgs) -> Result<Self, String> {
+        let mut vars = self.vars;
+        for pair in &args.set {
+            let (key, value) = pair.split_once('=')
+                .ok_or_else(|| tr!(init_bad_set, pair))?;
+            vars.insert(key.trim().to_string(), toml::Value::String(value.to_string()));
         }
+
+        Ok(Answers {
+            path: args.path.clone().or(self.path),
+            id: args.id.clone().or(self.id),
+            name: args.name.clone().or(self.name),
+            author: args.author.clone().or(self.author),
+            description: args.description.clone().or(self.description),
+            version: args.version.clone().or(self.version),
+            update_json: args.update_json.clone().or(self.update_json),
+            template: args.template.clone().or(self.template),
+            action: bool_flag(args.action, args.no_action).or(self.action),
+            webui: bool_flag(args.webui, args.no_webui).or(self.webui),
+            vars,
+        })
     }
 }
 
-fn create_changelog(base_path: &Path) {
-    let changelog_path = base_path.join("CHANGELOG.md");
-    if changelog_path.exists() {
-        println!("{}", format!("  [!] CHANGELOG.md 文件已存在，跳过创建").dimmed());
-    } else {
-        let changelog_content = "# 更新日志\n## v0.1.0\n";
-        fs::write(&changelog_path, changelog_content).expect("无法写入 CHANGELOG.md");
-        println!("{} 创建 CHANGELOG.md", "[+]".green());
+/// 已通过答案文件或 --set 预先回答的模板问题
+fn preset_var(presets: &BTreeMap<String, toml::Value>, key: &str) -> Option<String> {
+    match presets.get(key)? {
+        toml::Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
     }
 }
 
-fn create_action_script(base_path: &Path) {
-    let action_path = base_path.join("action.sh");
-    if action_path.exists() {
-        println!("{}", format!("  [!] action.sh 文件已存在，跳过创建").dimmed());
+fn bool_flag(on: bool, off: bool) -> Option<bool> {
+    if on {
+        Some(true)
+    } else if off {
+        Some(false)
     } else {
-        fs::write(&action_path, "#!/system/bin/sh\n# 执行按钮脚本\n").expect("无法写入 action.sh");
-        println!("{} 创建 action.sh", "[+]".green());
+        None
     }
 }
 
-fn create_webui(base_path: &Path) {
-    let webroot_path = base_path.join("webroot");
-    if webroot_path.exists() {
-        println!("{}", format!("  [!] webroot 目录已存在，跳过创建").dimmed());
-    } else {
-        fs::create_dir_all(&webroot_path).expect("无法创建 webroot 目录");
-        println!("{} 创建 webroot 目录", "[+]".green());
-    }
+/// 在有终端时提问，否则直接使用默认值
+struct Prompter {
+    interactive: bool,
+}
 
-    let index_html_path = webroot_path.join("index.html");
-    if index_html_path.exists() {
-        println!("{}", format!("  [!] index.html 文件已存在，跳过创建").dimmed());
-    } else {
-        let index_html = r#"<!DOCTYPE html>
-<html lang="zh-CN">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>KernelSU Module WebUI</title>
-    <style>
-        body { font-family: Arial, sans-serif; text-align: center; padding: 50px; }
-        h1 { color: #333; }
-    </style>
-</head>
-<body>
-    <h1>欢迎使用 KernelSU 模块</h1>
-    <p>这是一个简单的 WebUI 示例。</p>
-</body>
-</html>"#;
-        fs::write(&index_html_path, index_html).expect("无法写入 index.html");
-        println!("{} 创建 index.html", "[+]".green());
+impl Prompter {
+    fn input(&self, prompt: &str, default: Option<String>) -> Result<String, String> {
+        if !self.interactive {
+            return default.ok_or_else(|| tr!(init_no_terminal, prompt));
+        }
+
+        let mut input = Input::<String>::new().with_prompt(prompt);
+        if let Some(default) = default {
+            input = input.default(default);
+        }
+        input.interact_text().map_err(|e| e.to_string())
     }
-}
 
-fn create_ksmm_config(base_path: &Path) {
-    let ksmm_path = base_path.join(".ksmm");
-    if ksmm_path.exists() {
-        println!("{}", format!("  [!] .ksmm 目录已存在，跳过创建").dimmed());
-    } else {
-        fs::create_dir_all(&ksmm_path).expect("无法创建 .ksmm 目录");
-        println!("{} 创建 .ksmm 目录", "[+]".green());
+    fn confirm(&self, prompt: &str, default: bool) -> Result<bool, String> {
+        if !self.interactive {
+            return Ok(default);
+        }
+
+        Confirm::new()
+            .with_prompt(prompt)
+            .default(default)
+            .interact()
+            .map_err(|e| e.to_string())
     }
 
-    // 创建构建配置文件
-    let build_conf_path = ksmm_path.join("build.conf");
-    if build_conf_path.exists() {
-        println!("{}", format!("  [!] .ksmm/build.conf 文件已存在，跳过创建").dimmed());
-    } else {
-        let build_conf_content = r#"# KernelSU 模块构建配置文件
-# 控制哪些文件被复制到构建目录
-#
-# 语法说明:
-#   - 普通行: 忽略这些文件/目录 (支持通配符)
-#   - ! 开头的行: 强制包括这些文件/目录 (即使在忽略列表中)
-#   - # 开头的行: 注释
-#   - 空行: 被忽略
-#
-# 示例:
-#   *.log      # 忽略所有 .log 文件
-#   build/     # 忽略 build 目录
-#   !system/   # 强制包括 system 目录
-
-# 版本控制文件
-.git/
-.gitignore
-.github/
-
-# 构建产物
-build/
-target/
-*.zip
-*.tar.gz
-
-# 临时文件
-*.tmp
-*.bak
-*~
-
-# 日志文件
-*.log
-
-# IDE 和编辑器文件
-.vscode/
-.idea/
-*.swp
-*.swo
-
-# 操作系统文件
-.DS_Store
-Thumbs.db
-
-# 文档文件
-README.md
-CHANGELOG.md
-
-# 强制包括的核心模块文件
-!module.prop
-!system/
-!webroot/
-
-# 强制包括脚本文件
-!*.sh
-!action.sh
-
-# 强制包括配置文件
-!system.prop
-!sepolicy.rule
-"#;
-        fs::write(&build_conf_path, build_conf_content).expect("无法写入 .ksmm/build.conf");
-        println!("{} 创建 .ksmm/build.conf", "[+]".green());
+    fn select(&self, prompt: &str, choices: &[String], default: usize) -> Result<usize, String> {
+        if !self.interactive {
+            return Ok(default);
+        }
+
+        Select::new()
+            .with_prompt(prompt)
+            .items(choices)
+            .default(default)
+            .interact()
+            .map_err(|e| e.to_string())
     }
-}
 
-fn create_github_workflows(base_path: &Path) {
-        let workflows_path = base_path.join(".github/workflows");
+    /// 回答模板清单中的问题，结果写入占位符表
+    fn ask_template_prompts(&self, manifest: &Manifest, presets: &BTreeMap<String, toml::Value>, vars: &mut BTreeMap<String, String>) -> Result<(), String> {
+        for prompt in &manifest.prompts {
+            if let Some(when) = &prompt.when
+                && !Condition::parse(when)?.eval(vars)
+            {
+                continue;
+            }
 
-        // 如果目录不存在则创建；如果存在则继续（不要直接返回）
-        if !workflows_path.exists() {
-                match fs::create_dir_all(&workflows_path) {
-                        Ok(_) => println!("{} 创建 .github/workflows 目录", "[+]".green()),
-                        Err(e) => {
-                                println!("{} 创建 .github/workflows 目录失败: {}", "[-]".red(), e);
-                                return;
+            let default = prompt.default_value(vars);
+            let value = match preset_var(presets, &prompt.key) {
+                Some(preset) => prompt.check_answer(&preset)?,
+                None => match prompt.kind {
+                    PromptKind::Bool => {
+                        let default = default.as_deref() == Some("true");
+                        self.confirm(&prompt.message, default)?.to_string()
+                    }
+                    PromptKind::Choice => {
+                        let index = default.as_ref()
+                            .and_then(|d| prompt.choices.iter().position(|c| c == d))
+                            .unwrap_or(0);
+                        prompt.choices[self.select(&prompt.message, &prompt.choices, index)?].clone()
+                    }
+                    PromptKind::Text if self.interactive => {
+                        let mut input = Input::<String>::new().with_prompt(&prompt.message);
+                        if let Some(default) = default {
+                            input = input.default(default);
                         }
-                }
-        } else {
-                println!("{}", format!("  [!] .github/workflows 目录已存在，继续检查文件").dimmed());
+                        input
+                            .validate_with(|answer: &String| prompt.check_answer(answer).map(|_| ()))
+                            .interact_text()
+                            .map_err(|e| e.to_string())?
+                    }
+                    PromptKind::Text => {
+                        let value = self.input(&prompt.message, default)?;
+                        prompt.check_answer(&value)?
+                    }
+                },
+            };
+
+            vars.insert(prompt.key.clone(), value);
         }
 
-        // 始终尝试创建 ci.yml（如果文件已存在则跳过）
-        let ci_yml_path = workflows_path.join("ci.yml");
-        if ci_yml_path.exists() {
-                println!("{}", format!("  [!] .github/workflows/ci.yml 已存在，跳过创建").dimmed());
-                return;
-        }
+        Ok(())
+    }
+}
 
-        let ci_yml_content = r#"name: Release with KSMM
-
-on:
-  push:
-    tags:
-      - "v*"    # 当推送 tag 时触发，比如 v1.0.0
-
-permissions:
-  contents: write
-
-jobs:
-  release:
-    name: Build and Release
-    runs-on: ubuntu-latest
-
-    steps:
-      - name: "🧩 Checkout repository"
-        uses: actions/checkout@v4
-
-      - name: "🦀 Install Rust"
-        uses: dtolnay/rust-toolchain@stable
-
-      - name: "📦 Install ksmm"
-        run: cargo install ksmm
-
-      - name: "🏷️ Get tag name"
-        id: get_tag
-        run: |
-            TAG_NAME=${GITHUB_REF#refs/tags/}
-            echo "TAG_NAME=$TAG_NAME" >> $GITHUB_ENV
-            echo "✅ Detected tag: $TAG_NAME"
-
-      - name: ⚙️ Run ksmm build
-        run: |
-              ksmm build
-
-      - name: "📁 Show release contents"
-        run: |
-            echo "🪣 Build output:"
-            ls -R .ksmm/release || echo "❌ .ksmm/release not found"
-
-      - name: "🚀 Create GitHub Release"
-        uses: softprops/action-gh-release@v2
-        with:
-            tag_name: ${{ env.TAG_NAME }}
-            name: "Release ${{ env.TAG_NAME }}"
-            files: |
-                .ksmm/release/**
-        env:
-            GITHUB_TOKEN: ${{ secrets.GITHUB_TOKEN }}
-"#;
-
-        fs::write(&ci_yml_path, ci_yml_content).expect("无法写入 .github/workflows/ci.yml");
-        println!("{} 创建 .github/workflows/ci.yml", "[+]".green());
+/// 读取 zip 根目录中 module.prop 的 id
+fn read_zip_module_id(archive: &mut ZipArchive<fs::File>) -> Result<String, KsmmError> {
+    let mut module_prop = archive.by_name("module.prop")
+        .map_err(|_| KsmmError::Zip(tr!(import_no_module_prop).to_string()))?;
+    let mut content = String::new();
+    module_prop.read_to_string(&mut content)
+        .map_err(|e| KsmmError::Zip(tr!(import_extract_failed, e)))?;
+
+    Ok(ModuleProp::parse(&content).id()?.to_string())
 }
 
-fn update_gitignore(base_path: &Path) {
-    // 只在项目目录（base_path）内查找或创建 .gitignore
-    let gitignore_path = base_path.join(".gitignore");
+/// 解压模块 zip，返回顶层条目名称
+///
+/// 条目路径经过 `enclosed_name` 检查，任何试图写到项目目录之外的条目（zip-slip）都会导致导入失败。
+/// 符号链接在所有文件解压之后重新创建，目标必须是留在模块目录中的相对路径，且不能经过 zip 中的其他链接；
+/// 位于链接之下的条目同样被拒绝，因此不会有条目经过链接写到别处。
+fn extract_module_zip(archive: &mut ZipArchive<fs::File>, base_path: &Path) -> Result<BTreeSet<String>, String> {
+    let mut top_level = BTreeSet::new();
+
+    // 先检查所有条目，不安全的 zip 不会留下任何文件
+    let mut entries = Vec::new();
+    let mut symlinks = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let Some(rel_path) = entry.enclosed_name() else {
+            return Err(tr!(import_zip_slip, entry.name()));
+        };
+        if entry.is_symlink() {
+            let mut target = String::new();
+            entry.read_to_string(&mut target).map_err(|e| e.to_string())?;
+            symlinks.push((rel_path.clone(), PathBuf::from(target)));
+        }
+        entries.push(rel_path);
+    }
+    let links: BTreeSet<&Path> = symlinks.iter().map(|(link, _)| link.as_path()).collect();
+    for rel_path in &entries {
+        if let Some(link) = rel_path.ancestors().skip(1).find(|parent| links.contains(parent)) {
+            return Err(tr!(import_entry_through_symlink, rel_path.display(), link.display()));
+        }
+    }
+    for (link, target) in &symlinks {
+        if !symlink_stays_inside(link, target, &links) {
+            return Err(tr!(import_symlink_escapes, link.display(), target.display()));
+        }
+    }
 
-    // 读取或创建 .gitignore
-    let content = if gitignore_path.exists() {
-        match fs::read_to_string(&gitignore_path) {
-            Ok(c) => c,
-            Err(e) => {
-                println!("{}", format!("  [!] 无法读取 .gitignore: {}", e).dimmed());
-                return;
-            }
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let Some(rel_path) = entry.enclosed_name() else {
+            continue;
+        };
+
+        if let Some(first) = rel_path.components().next() {
+            top_level.insert(first.as_os_str().to_string_lossy().into_owned());
+        }
+        if entry.is_symlink() {
+            continue;
         }
-    } else {
-        // 如果不存在，创建一个空的内容
-        String::new()
-    };
 
-    let is_empty = content.is_empty();
+        let dst = base_path.join(&rel_path);
+        if entry.is_dir() {
+            fs::create_dir_all(&dst).map_err(|e| e.to_string())?;
+            continue;
+        }
 
-    // 检查是否已经包含 .ksmm/
-    let has_ksmm = content.lines().any(|line| line.trim() == ".ksmm/");
-    let has_build_conf = content.lines().any(|line| line.trim() == "!.ksmm/build.conf");
-    
-    if has_ksmm && has_build_conf {
-        println!("{}", format!("  [!] .gitignore 已包含 .ksmm/ 和 !.ksmm/build.conf，跳过添加").dimmed());
-    } else {
-        // 追加内容到 .gitignore
-        let mut new_content = content;
-        if !is_empty && !new_content.ends_with('\n') {
-            new_content.push('\n');
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
-        
-        if !has_ksmm {
-            new_content.push_str(".ksmm/\n");
+        let mut out = fs::File::create(&dst).map_err(|e| format!("{}: {}", dst.display(), e))?;
+        io::copy(&mut entry, &mut out).map_err(|e| format!("{}: {}", dst.display(), e))?;
+
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&dst, fs::Permissions::from_mode(mode & 0o777)).map_err(|e| e.to_string())?;
         }
-        if !has_build_conf {
-            new_content.push_str("!.ksmm/build.conf\n");
+
+        println!("{} {}", "[+]".green(), tr!(import_extracted, rel_path.display()));
+    }
+
+    for (rel_path, target) in symlinks {
+        let dst = base_path.join(&rel_path);
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
-        
-        match fs::write(&gitignore_path, new_content) {
-            Ok(_) => {
-                if is_empty {
-                    println!("{} 创建 .gitignore", "[+]".green());
-                } else {
-                    println!("{} 更新 .gitignore", "[+]".green());
+        build::create_symlink(&target, &dst).map_err(|e| format!("{}: {}", dst.display(), e))?;
+        println!("{} {}", "[+]".green(), tr!(build_create_symlink, rel_path.display(), target.display()));
+    }
+
+    Ok(top_level)
+}
+
+/// 从链接所在目录按字面解析相对目标，是否始终不离开模块目录
+///
+/// 经过 `links` 中其他链接的目标无法按字面解析（例如 `d/x -> ..` 之后的 `d/x/..`），一律视为离开。
+fn symlink_stays_inside(link: &Path, target: &Path, links: &BTreeSet<&Path>) -> bool {
+    let mut path: PathBuf = link.parent().map(Path::to_path_buf).unwrap_or_default();
+    let mut components = target.components().peekable();
+    while let Some(component) = components.next() {
+        match component {
+            Component::Normal(name) => {
+                path.push(name);
+                // 目标本身可以是另一个链接，它的目标另外检查
+                if components.peek().is_some() && links.contains(path.as_path()) {
+                    return false;
                 }
             }
-            Err(e) => {
-                println!("{}", format!("  [!] 无法写入 .gitignore: {}", e).dimmed());
-            }
+            Component::CurDir => {}
+            Component::ParentDir if path.pop() => {}
+            _ => return false,
         }
     }
+    true
 }
 
-pub fn execute() {
-    println!("{} {}", "🚀", "初始化 KernelSU 模块...".cyan());
+/// 让 build.conf 强制包括从 zip 导入的所有顶层文件，保证重新构建出等价的 zip
+fn include_imported_entries(base_path: &Path, top_level: &BTreeSet<String>) -> io::Result<()> {
+    let build_conf_path = base_path.join(".ksmm/build.conf");
+    let mut content = fs::read_to_string(&build_conf_path)?;
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+
+    content.push_str(&format!("\n# {}\n", tr!(import_build_conf_comment)));
+    for name in top_level {
+        if base_path.join(name).is_dir() {
+            content.push_str(&format!("!/{}/\n", name));
+        } else {
+            content.push_str(&format!("!/{}\n", name));
+        }
+    }
+
+    fs::write(&build_conf_path, content)
+}
+
+fn import_from_zip(zip_path: &Path, answers: &Answers, prompter: &Prompter) -> Result<(), KsmmError> {
+    println!("📦 {}", tr!(import_start, zip_path.display()));
+
+    let file = fs::File::open(zip_path).map_err(|e| KsmmError::io(tr!(import_open_failed, e), e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| KsmmError::Zip(tr!(import_open_failed, e)))?;
+
+    let id = read_zip_module_id(&mut archive)?;
+
+    // 输入创建地址，默认使用模块 id
+    let path = match &answers.path {
+        Some(path) => path.clone(),
+        None => prompter.input(tr!(init_prompt_path), Some(id.clone()))
+            .map_err(|e| KsmmError::Input(tr!(init_read_path_failed, e)))?,
+    };
+    let base_path = Path::new(&path);
+
+    // 只导入到空目录，避免与已有文件混在一起
+    if base_path.read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(KsmmError::Input(tr!(import_dir_not_empty, base_path.display())));
+    }
+    fs::create_dir_all(base_path).map_err(|e| KsmmError::io(tr!(init_create_dir_failed, e), e))?;
+
+    let top_level = extract_module_zip(&mut archive, base_path)
+        .map_err(|e| KsmmError::Zip(tr!(import_extract_failed, e)))?;
+
+    // 添加 ksmm 项目文件
+    let mut template = Template::empty("import");
+    template.add_layer(PROJECT_LAYER);
+    template.render_into(base_path, &BTreeMap::new())
+        .map_err(|e| KsmmError::io(tr!(init_project_files_failed, e), e))?;
+    include_imported_entries(base_path, &top_level)
+        .map_err(|e| KsmmError::io(tr!(import_build_conf_failed, e), e))?;
+    update_gitignore(base_path);
+
+    println!("✅ {}", tr!(import_done).cyan());
+    println!();
+    println!("📁 {}", tr!(init_summary_path, base_path.canonicalize().unwrap_or(base_path.to_path_buf()).display().green()));
+    println!("🔧 {}", tr!(init_summary_id, id.green()));
+    println!();
+    println!("📋 {}", tr!(import_next, "'ksmm build'".green()));
+    Ok(())
+}
+
+fn list_templates() {
+    println!("📦 {}", tr!(templates_builtin));
+    for template in BUILTIN_TEMPLATES {
+        println!("  {:<12} {}", template.name.green(), (template.description)(i18n::catalog()));
+    }
+    println!();
+    println!("{} {}", "💡".blue(), tr!(templates_local_hint));
+}
+
+pub fn execute(args: InitArgs) -> Result<(), KsmmError> {
+    if args.list_templates {
+        list_templates();
+        return Ok(());
+    }
+
+    println!("🚀 {}", tr!(init_start).cyan());
+
+    let answers = match &args.answers {
+        Some(answers_path) => Answers::load(answers_path)
+            .map_err(|e| KsmmError::Input(tr!(init_answers_failed, e)))?,
+        None => Answers::default(),
+    };
+    let answers = answers.merge_args(&args).map_err(KsmmError::Input)?;
+
+    // 先解析模板，避免创建到一半才发现模板不存在
+    let template = match &answers.template {
+        Some(name) => Template::resolve(name).map_err(KsmmError::Template)?,
+        None => Template::from_builtin(&BUILTIN_TEMPLATES[0]),
+    };
+    let prompter = Prompter { interactive: !args.yes && io::stdin().is_terminal() };
+
+    if let Some(zip_path) = &args.from_zip {
+        return import_from_zip(zip_path, &answers, &prompter);
+    }
 
     // 输入创建地址
-    let path: String = Input::new()
-        .with_prompt("请输入创建地址 (默认当前目录: ksmm)")
-        .default("ksmm".to_string())
-        .interact_text()
-        .unwrap();
+    let path = match answers.path {
+        Some(path) => path,
+        None => {
+            let default_path = answers.id.clone().unwrap_or_else(|| "ksmm".to_string());
+            prompter.input(tr!(init_prompt_path), Some(default_path))
+                .map_err(|e| KsmmError::Input(tr!(init_read_path_failed, e)))?
+        }
+    };
 
     let base_path = Path::new(&path);
 
-    // 确定项目名称
-    let mut project_name = if path == "." {
-        // 使用当前目录名称
-        match std::env::current_dir() {
-            Ok(current_dir) => {
-                current_dir.file_name()
+    // 验证项目名称/id格式
+    let id = match answers.id {
+        Some(id) => id,
+        None => {
+            // 确定项目名称
+            let project_name = if path == "." {
+                // 使用当前目录名称
+                match std::env::current_dir() {
+                    Ok(current_dir) => {
+                        current_dir.file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("unknown")
+                            .to_string()
+                    }
+                    Err(_) => "unknown".to_string(),
+                }
+            } else {
+                base_path.file_name()
                     .and_then(|n| n.to_str())
                     .unwrap_or("unknown")
                     .to_string()
+            };
+
+            if is_valid_id(&project_name) {
+                project_name
+            } else {
+                println!("⚠️ {}", tr!(init_dir_name_invalid, project_name));
+                prompter.input(tr!(init_prompt_id), None)
+                    .map_err(|e| KsmmError::Input(tr!(init_read_id_failed, e)))?
             }
-            Err(_) => "unknown".to_string(),
         }
-    } else {
-        base_path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string()
     };
 
-    // 验证项目名称/id格式
-    let id_regex = Regex::new(r"^[a-zA-Z][a-zA-Z0-9._-]+$").unwrap();
-    if !id_regex.is_match(&project_name) {
-        println!("{} 当前目录名称 '{}' 不符合模块ID格式要求。", "⚠️", project_name);
-        project_name = Input::new()
-            .with_prompt("请输入项目名称 (必须以字母开头，只能包含字母、数字、点、下划线和连字符)")
-            .interact_text()
-            .unwrap();
-        
-        // 再次验证用户输入
-        if !id_regex.is_match(&project_name) {
-            println!("{} 模块ID格式无效。必须以字母开头，只能包含字母、数字、点、下划线和连字符。", "❌");
-            return;
-        }
+    if !is_valid_id(&id) {
+        return Err(KsmmError::Input(tr!(init_invalid_id).to_string()));
     }
 
-    // 使用项目名称作为id和name
-    let id = project_name.clone();
-    let name = project_name;
+    // 未指定名称时使用 id
+    let name = answers.name.unwrap_or_else(|| id.clone());
 
     // 获取git仓库信息
-    let (update_json, git_username, branch, remote_url, status) = get_git_info();
+    let (git_update_json, git_username, branch, remote_url, status) = get_git_info();
 
     // 输出git信息
     if remote_url.is_some() || branch.is_some() {
-        println!("{} 检测到 Git 仓库", "🔍");
+        println!("🔍 {}", tr!(git_detected));
         if let Some(branch_name) = &branch {
-            println!("  {}: {}", "分支".blue(), branch_name.green());
+            println!("  {}: {}", tr!(git_branch).blue(), branch_name.green());
         }
         if let Some(url) = &remote_url {
-            println!("  {}: {}", "远程仓库".blue(), url.green());
+            println!("  {}: {}", tr!(git_remote).blue(), url.green());
         }
         if let Some(user) = &git_username {
-            println!("  {}: {}", "用户名".blue(), user.green());
+            println!("  {}: {}", tr!(git_user).blue(), user.green());
         }
         if let Some(url) = &remote_url {
             let github_regex = Regex::new(r"github\.com[\/:]([^\/]+)\/([^\/\.]+)").unwrap();
-            if let Some(captures) = github_regex.captures(url) {
-                if let Some(repo) = captures.get(2) {
-                    let repo_name = repo.as_str().trim_end_matches(".git");
-                    println!("  {}: {}", "仓库".blue(), repo_name.green());
-                }
+            if let Some(captures) = github_regex.captures(url)
+                && let Some(repo) = captures.get(2)
+            {
+                let repo_name = repo.as_str().trim_end_matches(".git");
+                println!("  {}: {}", tr!(git_repo).blue(), repo_name.green());
             }
         }
         if let Some(work_status) = &status {
-            println!("  {}: {}", "状态".blue(), work_status.green());
+            println!("  {}: {}", tr!(git_status).blue(), work_status.green());
         }
         println!();
     }
 
-    // 确定作者
-    let author = if let Some(username) = git_username {
-        username
-    } else {
-        println!("{} 无法获取git用户信息，使用默认作者: ksmm", "ℹ️".blue());
-        "ksmm".to_string()
+    // 确定作者：命令行 > KSMM_AUTHOR 与用户配置中的 init.author > git 用户名
+    // 有问题的用户配置或 KSMM_* 环境变量不应妨碍创建项目，只给出警告
+    let configured_author = match Config::load(None, &Overrides::default()) {
+        Ok(config) => config.init.author,
+        Err(e) => {
+            println!("{} {}", "⚠️".yellow(), tr!(init_config_ignored, e));
+            None
+        }
+    };
+    let author = match (answers.author.or(configured_author), git_username) {
+        (Some(author), _) => author,
+        (None, Some(username)) => username,
+        (None, None) => {
+            println!("{} {}", "ℹ️".blue(), tr!(init_default_author));
+            "ksmm".to_string()
+        }
     };
 
     // 默认版本信息
-    let version = "0.1.0".to_string();
-    let now = Utc::now();
-    let version_code_int = (now.year() * 1000000 + now.month() as i32 * 10000 + now.day() as i32 * 100 + now.hour() as i32) as i32;
+    let version = answers.version.unwrap_or_else(|| "0.1.0".to_string());
+    let version_code = timestamp_version_code();
 
     // 自动生成描述
-    let description = format!("一个用ksmm创建的{}模块", name);
+    let description = answers.description.unwrap_or_else(|| tr!(init_default_description, name));
+
+    let spec = ModuleSpec {
+        id,
+        name,
+        version,
+        version_code,
+        author,
+        description,
+        update_json: answers.update_json.unwrap_or(git_update_json),
+    };
+
+    // 回答模板清单中的自定义问题
+    let mut vars = spec.template_vars();
+    prompter.ask_template_prompts(&template.manifest, &answers.vars, &mut vars)
+        .map_err(|e| KsmmError::Input(tr!(init_template_prompts_failed, e)))?;
 
     // 确保项目目录存在
     if !base_path.exists() {
-        if let Err(e) = fs::create_dir_all(base_path) {
-            println!("{} 创建项目目录失败: {}", "❌", e);
-            return;
-        }
+        fs::create_dir_all(base_path).map_err(|e| KsmmError::io(tr!(init_create_dir_failed, e), e))?;
     }
 
-    // 创建 system 目录
-    create_system_directory(base_path);
-
-    // 创建 .ksmm 配置目录
-    create_ksmm_config(base_path);
-
-    // 更新 .gitignore 文件
-    update_gitignore(base_path);
-
-    // 创建 GitHub Actions CI 工作流
-    create_github_workflows(base_path);
-
-    // 创建 module.prop
-    create_module_prop(base_path, &id, &name, &version, version_code_int, &author, &description, &update_json);
-
-    // 创建脚本文件
-    create_script_files(base_path);
-
-    // 创建 CHANGELOG.md
-    create_changelog(base_path);
+    // 未指定模板时，按照回答决定是否附加执行按钮和 WebUI
+    let mut template = template;
+    let explicit_template = answers.template.is_some();
 
     // 检查是否需要执行按钮
-    let action_path = base_path.join("action.sh");
-    if action_path.exists() {
-        println!("{}", format!("  [!] action.sh 文件已存在，跳过执行按钮配置").dimmed());
+    if base_path.join("action.sh").exists() {
+        println!("{}", format!("  [!] {}", tr!(init_action_exists)).dimmed());
     } else {
-        let need_action = Confirm::new()
-            .with_prompt("是否需要执行按钮?")
-            .default(true)
-            .interact()
-            .unwrap();
+        let need_action = match answers.action {
+            Some(action) => action,
+            None if explicit_template => false,
+            None => prompter.confirm(tr!(init_prompt_action), true)
+                .map_err(|e| KsmmError::Input(tr!(init_read_action_failed, e)))?,
+        };
 
         if need_action {
-            create_action_script(base_path);
+            template.add_layer(ACTION_LAYER);
         }
     }
 
     // 检查是否需要 webui
-    let webroot_path = base_path.join("webroot");
-    if webroot_path.exists() {
-        println!("{}", format!("  [!] webroot 目录已存在，跳过WebUI配置").dimmed());
+    if base_path.join("webroot").exists() {
+        println!("{}", format!("  [!] {}", tr!(init_webui_exists)).dimmed());
     } else {
-        let need_webui = Confirm::new()
-            .with_prompt("是否需要 WebUI?")
-            .default(true)
-            .interact()
-            .unwrap();
+        let need_webui = match answers.webui {
+            Some(webui) => webui,
+            None if explicit_template => false,
+            None => prompter.confirm(tr!(init_prompt_webui), true)
+                .map_err(|e| KsmmError::Input(tr!(init_read_webui_failed, e)))?,
+        };
 
         if need_webui {
-            create_webui(base_path);
+            template.add_layer(WEBUI_LAYER);
         }
     }
 
-    println!("{} {}", "✅", "模块初始化完成!".cyan());
+    // 渲染模板文件
+    match &template.manifest.description {
+        Some(description) => println!("📦 {} ({})", tr!(init_using_template, template.name.green()), description),
+        None => println!("📦 {}", tr!(init_using_template, template.name.green())),
+    }
+    template.render_into(base_path, &vars).map_err(|e| KsmmError::io(tr!(init_render_failed, e), e))?;
+
+    // 更新 .gitignore 文件
+    update_gitignore(base_path);
+
+    println!("✅ {}", tr!(init_done).cyan());
     println!();
-    println!("{} 项目路径: {}", "📁", base_path.canonicalize().unwrap_or(base_path.to_path_buf()).display().green());
-    println!("{} 项目ID: {}", "🔧", id.green());
+    println!("📁 {}", tr!(init_summary_path, base_path.canonicalize().unwrap_or(base_path.to_path_buf()).display().green()));
+    println!("🔧 {}", tr!(init_summary_id, spec.id.green()));
     println!();
-    println!("{} 下一步:", "📋");
-    println!("  1. 编辑 {} 目录，添加你要修改的系统文件", "system/".green());
-    println!("  2. 根据需要修改 {} 安装脚本", "customize.sh".green());
-    println!("  3. 运行 {} 构建模块", "'ksmm build'".green());
+    println!("📋 {}", tr!(init_next));
+    println!("  1. {}", tr!(init_next_system, "system/".green()));
+    println!("  2. {}", tr!(init_next_customize, "customize.sh".green()));
+    println!("  3. {}", tr!(init_next_build, "'ksmm build'".green()));
     println!();
-    println!("{} 项目初始化成功!", "🎉");
-}
\ No newline at end of file
+    println!("🎉 {}", tr!(init_success));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    /// 写出只有 module.prop 与按顺序排列的符号链接的 zip
+    fn module_zip(dir: &Path, links: &[(&str, &str)]) -> ZipArchive<fs::File> {
+        let path = dir.join("module.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        zip.start_file("module.prop", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"id=demo\n").unwrap();
+        for (link, target) in links {
+            zip.add_symlink(*link, *target, SimpleFileOptions::default()).unwrap();
+        }
+        zip.finish().unwrap();
+        ZipArchive::new(fs::File::open(&path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn symlinks_inside_module_are_recreated() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut archive = module_zip(dir.path(), &[("system/bin/tool", "../../module.prop")]);
+        let base = dir.path().join("out");
+
+        let top_level = extract_module_zip(&mut archive, &base).unwrap();
+        assert!(top_level.contains("system"));
+        assert_eq!(fs::read_link(base.join("system/bin/tool")).unwrap(), PathBuf::from("../../module.prop"));
+    }
+
+    #[test]
+    fn escaping_symlinks_are_rejected() {
+        for target in ["../../../etc/passwd", "/system/bin/sh"] {
+            let dir = tempfile::tempdir().unwrap();
+            let mut archive = module_zip(dir.path(), &[("system/bin/tool", target)]);
+            let base = dir.path().join("out");
+
+            assert!(extract_module_zip(&mut archive, &base).is_err(), "{}", target);
+            assert!(!base.join("module.prop").exists());
+        }
+    }
+
+    #[test]
+    fn chained_symlinks_are_rejected() {
+        let chains: [&[(&str, &str)]; 3] = [
+            &[("x", "."), ("x/y", "..")],
+            &[("a/b", ".."), ("a/b/c", "../..")],
+            &[("d/x", ".."), ("y", "d/x/..")],
+        ];
+        for links in chains {
+            let dir = tempfile::tempdir().unwrap();
+            let mut archive = module_zip(dir.path(), links);
+            let base = dir.path().join("out");
+
+            assert!(extract_module_zip(&mut archive, &base).is_err(), "{:?}", links);
+            assert!(!base.exists());
+        }
+    }
+}
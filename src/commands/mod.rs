@@ -1,4 +1,8 @@
 pub mod build;
+pub mod bump;
+pub mod changelog;
+pub mod check;
+pub mod convert;
 pub mod init;
 pub mod sign;
 pub mod version;
\ No newline at end of file
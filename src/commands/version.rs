@@ -6,7 +6,7 @@ pub fn execute() {
 
     // Try to get git SHA
     let git_sha = match Command::new("git")
-        .args(&["rev-parse", "--short=7", "HEAD"])
+        .args(["rev-parse", "--short=7", "HEAD"])
         .output()
     {
         Ok(output) if output.status.success() => {
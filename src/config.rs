@@ -0,0 +1,583 @@
+use regex::Regex;
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use crate::error::KsmmError;
+use crate::i18n::tr;
+use crate::ignore;
+
+/// 项目配置文件，相对于项目根目录
+pub const CONFIG_FILE: &str = ".ksmm/ksmm.toml";
+
+/// 合并后的配置，优先级从高到低为：命令行参数、`KSMM_*` 环境变量、项目配置 `.ksmm/ksmm.toml`、
+/// 用户配置 `~/.config/ksmm/config.toml`、默认值
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub build: BuildConfig,
+    pub sign: SignConfig,
+    pub init: InitConfig,
+    /// 构建时如何生成 versionCode
+    pub version_code: VersionCodeStrategy,
+    /// 打包时覆盖的文件权限
+    pub permissions: PermissionMap,
+    /// update.json 中的下载地址与更新日志地址
+    pub update_json: UpdateJsonConfig,
+    /// 按名称排列的构建变体
+    pub variants: BTreeMap<String, VariantConfig>,
+    /// `bin/<abi>/` 中按架构区分的可执行文件
+    pub abi: AbiConfig,
+    /// 构建模块之前用 cargo 交叉编译的可执行文件
+    pub native: NativeConfig,
+}
+
+/// `[build]` 表
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildConfig {
+    /// 复制符号链接指向的内容，而不是在 zip 中保留链接
+    pub follow_symlinks: bool,
+    /// 构建目录，相对于项目根目录，必须位于 `.ksmm/` 中或被打包规则忽略，见 [`crate::Project::open_with_overrides`]
+    pub build_dir: PathBuf,
+    /// 发布目录，规则与 `build_dir` 相同
+    pub release_dir: PathBuf,
+    /// 发布 zip 的文件名模板，占位符见 [`crate::Packager::artifact_name`]
+    pub artifact_name: String,
+    /// Deflate 压缩级别 0–9，0 表示不压缩
+    pub compression_level: u8,
+}
+
+impl Default for BuildConfig {
+    fn default() -> Self {
+        BuildConfig {
+            follow_symlinks: false,
+            build_dir: PathBuf::from(".ksmm/build"),
+            release_dir: PathBuf::from(".ksmm/release"),
+            artifact_name: "{id}-{versionCode}{signed}.zip".to_string(),
+            compression_level: 6,
+        }
+    }
+}
+
+/// `[sign]` 表
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SignConfig {
+    /// 私钥文件，未设置时使用 .ksmm/key 中的第一个 .pem 文件
+    pub key: Option<PathBuf>,
+}
+
+/// `[init]` 表，通常写在用户配置中
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InitConfig {
+    /// `ksmm init` 的默认作者，优先于 git 用户名
+    pub author: Option<String>,
+}
+
+/// `[update_json]` 表，模板中可以使用 [`crate::update_json`] 列出的占位符
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdateJsonConfig {
+    /// 提供默认模板的代码托管平台
+    pub forge: Forge,
+    /// 仓库地址，例如 `https://git.example.com/me/my-module`，默认从 module.prop 的 updateJson 推断
+    pub repository: Option<String>,
+    /// `static` 预设中文件所在的目录，默认为 updateJson 所在的目录
+    pub base_url: Option<String>,
+    /// `{branch}` 的值，默认为当前分支，分离 HEAD 时（例如在 CI 中构建标签）为 main
+    pub branch: Option<String>,
+    /// 覆盖预设中的 zipUrl 模板
+    pub zip_url: Option<String>,
+    /// 覆盖预设中的 changelog 模板
+    pub changelog: Option<String>,
+    /// 写入 zip 的 SHA-256，供镜像校验下载的文件
+    pub sha256: bool,
+    /// 额外写入的字段，值同样是模板
+    pub extra: BTreeMap<String, String>,
+}
+
+/// `[variants.<name>]` 表，一个变体从同一份源码构建出另一个 zip
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VariantConfig {
+    /// 覆盖目录，相对于项目根目录，其中的文件在复制之后覆盖构建目录中的同名文件
+    pub overlay: Option<PathBuf>,
+    /// 额外的打包规则，语法与 build.conf 相同，优先于 build.conf
+    pub rules: Vec<String>,
+    /// 追加到模块 ID 之后的后缀
+    pub id_suffix: Option<String>,
+    /// 覆盖 module.prop 中的字段，例如 `name` 或 `updateJson`
+    pub module_prop: BTreeMap<String, String>,
+}
+
+/// `[abi]` 表
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AbiConfig {
+    /// 必须提供可执行文件的架构，缺少目录或其中缺少其他架构提供的文件时构建失败
+    pub abis: Vec<Abi>,
+}
+
+/// `[native]` 表
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NativeConfig {
+    /// Cargo.toml 的路径，相对于项目根目录
+    pub manifest: PathBuf,
+    /// 要构建的 cargo 包，每个包的所有可执行目标都会被打包
+    pub packages: Vec<String>,
+    /// Rust 目标三元组，例如 `aarch64-linux-android`
+    pub targets: Vec<String>,
+    /// 可执行文件在模块中的目录，支持 `{abi}` 与 `{target}` 占位符
+    pub dest: String,
+    /// 以 release 配置构建时去掉符号
+    pub strip: bool,
+}
+
+impl Default for NativeConfig {
+    fn default() -> Self {
+        NativeConfig {
+            manifest: PathBuf::from("Cargo.toml"),
+            packages: Vec::new(),
+            targets: Vec::new(),
+            dest: "bin/{abi}".to_string(),
+            strip: false,
+        }
+    }
+}
+
+/// Android 设备架构，配置与目录名可以使用 ABI 名称或安装时 `$ARCH` 的值
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+pub enum Abi {
+    #[serde(rename = "arm64-v8a", alias = "arm64")]
+    Arm64,
+    #[serde(rename = "armeabi-v7a", alias = "arm")]
+    Arm,
+    #[serde(rename = "x86_64", alias = "x64")]
+    X64,
+    #[serde(rename = "x86")]
+    X86,
+}
+
+/// `[update_json]` 表中的 `forge`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Forge {
+    #[default]
+    Github,
+    Gitlab,
+    Gitea,
+    Forgejo,
+    /// 任意静态文件服务器，zip 与 CHANGELOG.md 和 update.json 放在同一目录
+    Static,
+}
+
+/// `[version_code]` 表中的 `strategy`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "strategy", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum VersionCodeStrategy {
+    /// 当前 UTC 时间，YYYYMMDDHH
+    #[default]
+    Timestamp,
+    /// 2100000000 加上自 2026 年以来的分钟数，同一小时内多次构建也不会重复，且大于 `Timestamp` 的值
+    ///
+    /// 数值不可读且接近 i32 上限，之后换成其他策略需要用 `ksmm bump --version-code` 重设。
+    TimestampMinutes,
+    /// 保留 module.prop 中的值
+    Keep,
+    /// module.prop 中的值加一
+    Increment,
+    /// 当前分支的提交数
+    GitCount,
+    /// 由 `version` 计算：major * 10000 + minor * 100 + patch
+    Semver,
+    /// 读取环境变量，例如 CI 的构建编号
+    Env { env: String },
+}
+
+impl Config {
+    /// 合并各层配置，`root` 为 `None` 时（例如 `ksmm init`）没有项目配置
+    ///
+    /// 每一层单独检查，错误信息中会指出出错的文件或环境变量。
+    pub fn load(root: Option<&Path>, cli: &Overrides) -> Result<Self, KsmmError> {
+        let mut merged = toml::Table::new();
+        if let Some(path) = user_config_path()
+            && let Some(table) = read_layer(&path, &path.display().to_string())?
+        {
+            merge(&mut merged, table);
+        }
+        if let Some(root) = root
+            && let Some(table) = read_layer(&root.join(CONFIG_FILE), CONFIG_FILE)?
+        {
+            merge(&mut merged, table);
+        }
+        for (table, source) in [(Overrides::from_env()?.table, tr!(config_source_env)), (cli.table.clone(), tr!(config_source_cli))] {
+            check_layer(&table, source)?;
+            merge(&mut merged, table);
+        }
+
+        let config: Config = check_layer(&merged, tr!(config_source_merged))?;
+        if config.build.compression_level > 9 {
+            return Err(KsmmError::Config(tr!(config_bad_compression_level, config.build.compression_level)));
+        }
+        // 变体名会出现在文件名中
+        if let Some(name) = config.variants.keys().find(|name| !is_valid_variant_name(name)) {
+            return Err(KsmmError::Config(tr!(config_bad_variant_name, name)));
+        }
+        if let Some((name, _)) = config.variants.iter().find(|(_, variant)| variant.module_prop.contains_key("versionCode")) {
+            return Err(KsmmError::Config(tr!(config_variant_version_code, name)));
+        }
+        if !config.native.packages.is_empty() && config.native.targets.is_empty() {
+            return Err(KsmmError::Config(tr!(config_native_no_targets).to_string()));
+        }
+        Ok(config)
+    }
+}
+
+/// 变体名只能包含字母、数字、`-`、`_` 与 `.`，且不能以 `.` 开头
+fn is_valid_variant_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// 展开模板时的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TemplateError {
+    /// `{` 没有对应的 `}`
+    Unclosed,
+    /// 没有值的占位符
+    Unknown(String),
+}
+
+/// 替换模板中的 `{name}` 占位符
+pub(crate) fn expand_template<'a>(template: &str, lookup: impl Fn(&str) -> Option<&'a str>) -> Result<String, TemplateError> {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find('}').ok_or(TemplateError::Unclosed)?;
+        let name = &after[..end];
+        result.push_str(lookup(name).ok_or_else(|| TemplateError::Unknown(name.to_string()))?);
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// 用户配置文件：`$XDG_CONFIG_HOME/ksmm/config.toml`，默认为 `~/.config/ksmm/config.toml`
+pub fn user_config_path() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir).join("ksmm/config.toml")),
+        None => home_dir().map(|home| home.join(".config/ksmm/config.toml")),
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).filter(|dir| !dir.is_empty()).map(PathBuf::from)
+}
+
+/// 展开开头的 `~/`
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// 不访问文件系统地将路径转换为绝对路径，并去掉其中的 `.` 与 `..`
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// 读取一个配置文件，文件不存在时返回 `None`
+fn read_layer(path: &Path, source: &str) -> Result<Option<toml::Table>, KsmmError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(KsmmError::io(tr!(read_file_failed, path.display(), e), e)),
+    };
+    let table: toml::Table = toml::from_str(&content).map_err(|e| KsmmError::Config(tr!(config_invalid, source, e)))?;
+    check_layer(&table, source)?;
+    Ok(Some(table))
+}
+
+/// 按完整配置解析一层，确保其中没有未知的键或错误的类型
+fn check_layer(table: &toml::Table, source: &str) -> Result<Config, KsmmError> {
+    toml::Value::Table(table.clone())
+        .try_into()
+        .map_err(|e| KsmmError::Config(tr!(config_invalid, source, e)))
+}
+
+/// 将 `upper` 合并到 `lower`：表逐键合并，其他值直接覆盖
+///
+/// `[version_code]` 的字段取决于 `strategy`，总是整体替换。
+fn merge(lower: &mut toml::Table, upper: toml::Table) {
+    for (key, value) in upper {
+        match (lower.get_mut(&key), value) {
+            (Some(toml::Value::Table(lower)), toml::Value::Table(upper)) if key != "version_code" => merge(lower, upper),
+            (_, value) => {
+                lower.insert(key, value);
+            }
+        }
+    }
+}
+
+/// 环境变量中值的类型
+#[derive(Clone, Copy)]
+enum EnvKind {
+    String,
+    Integer,
+    Bool,
+    /// 策略名，`env:NAME` 表示读取环境变量 NAME
+    VersionCode,
+}
+
+/// 支持的 `KSMM_*` 环境变量与对应的配置项
+const ENV_VARS: &[(&str, &str, EnvKind)] = &[
+    ("KSMM_BUILD_DIR", "build.build_dir", EnvKind::String),
+    ("KSMM_RELEASE_DIR", "build.release_dir", EnvKind::String),
+    ("KSMM_ARTIFACT_NAME", "build.artifact_name", EnvKind::String),
+    ("KSMM_COMPRESSION_LEVEL", "build.compression_level", EnvKind::Integer),
+    ("KSMM_FOLLOW_SYMLINKS", "build.follow_symlinks", EnvKind::Bool),
+    ("KSMM_VERSION_CODE", "version_code", EnvKind::VersionCode),
+    ("KSMM_SIGN_KEY", "sign.key", EnvKind::String),
+    ("KSMM_AUTHOR", "init.author", EnvKind::String),
+    ("KSMM_FORGE", "update_json.forge", EnvKind::String),
+    ("KSMM_ZIP_URL", "update_json.zip_url", EnvKind::String),
+    ("KSMM_CHANGELOG_URL", "update_json.changelog", EnvKind::String),
+];
+
+/// 按 `build.release_dir` 这样的键设置的一层配置，用于环境变量与命令行参数
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    table: toml::Table,
+}
+
+impl Overrides {
+    /// 设置以 `.` 分隔的键
+    pub fn set(&mut self, key: &str, value: impl Into<toml::Value>) -> &mut Self {
+        let mut table = &mut self.table;
+        let mut parts = key.split('.').peekable();
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                table.insert(part.to_string(), value.into());
+                break;
+            }
+            let entry = table.entry(part.to_string()).or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            if !entry.is_table() {
+                *entry = toml::Value::Table(toml::Table::new());
+            }
+            table = entry.as_table_mut().expect("just replaced with a table");
+        }
+        self
+    }
+
+    /// 设置 versionCode 策略，`env:NAME` 表示读取环境变量 NAME
+    pub fn set_version_code(&mut self, strategy: &str) -> &mut Self {
+        let mut table = toml::Table::new();
+        match strategy.split_once(':') {
+            Some(("env", name)) => {
+                table.insert("strategy".to_string(), "env".into());
+                table.insert("env".to_string(), name.into());
+            }
+            _ => {
+                table.insert("strategy".to_string(), strategy.into());
+            }
+        }
+        self.set("version_code", table)
+    }
+
+    /// 读取 `KSMM_*` 环境变量
+    pub fn from_env() -> Result<Self, KsmmError> {
+        let mut overrides = Overrides::default();
+        for &(name, key, kind) in ENV_VARS {
+            let Ok(value) = env::var(name) else {
+                continue;
+            };
+            let invalid = || KsmmError::Config(tr!(config_bad_env, name, value));
+            match kind {
+                EnvKind::String => overrides.set(key, value.as_str()),
+                EnvKind::Integer => overrides.set(key, value.trim().parse::<i64>().map_err(|_| invalid())?),
+                EnvKind::Bool => match value.trim() {
+                    "1" | "true" | "yes" | "on" => overrides.set(key, true),
+                    "0" | "false" | "no" | "off" | "" => overrides.set(key, false),
+                    _ => return Err(invalid()),
+                },
+                EnvKind::VersionCode => overrides.set_version_code(value.trim()),
+            };
+        }
+        Ok(overrides)
+    }
+}
+
+/// `[permissions]` 表：键是相对于模块根目录的通配符模式，值是八进制权限
+///
+/// 以 `/` 结尾的模式只匹配目录，其余模式只匹配文件；多个模式匹配同一路径时，文件中靠后的优先。
+#[derive(Debug, Clone, Default)]
+pub struct PermissionMap {
+    entries: Vec<PermissionEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct PermissionEntry {
+    dir_only: bool,
+    regex: Regex,
+    mode: u32,
+}
+
+impl PermissionMap {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 返回路径（以 `/` 分隔，相对于模块根目录）对应的权限，没有模式匹配时返回 `None`
+    pub fn mode_for(&self, path: &str, is_dir: bool) -> Option<u32> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.dir_only == is_dir && entry.regex.is_match(path))
+            .map(|entry| entry.mode)
+    }
+
+    fn insert(&mut self, pattern: &str, mode: u32) -> Result<(), String> {
+        let (dir_only, glob) = match pattern.strip_suffix('/') {
+            Some(glob) => (true, glob),
+            None => (false, pattern),
+        };
+        let regex = ignore::glob_regex(glob.trim_start_matches('/')).map_err(|e| e.to_string())?;
+        self.entries.push(PermissionEntry { dir_only, regex, mode });
+        Ok(())
+    }
+}
+
+/// 权限可以写成字符串 `"0644"` 或 TOML 的八进制整数 `0o644`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ModeValue {
+    Octal(String),
+    Integer(u32),
+}
+
+impl ModeValue {
+    fn mode(&self) -> Option<u32> {
+        let mode = match self {
+            ModeValue::Octal(text) => {
+                let digits = text.trim().trim_start_matches("0o");
+                u32::from_str_radix(digits, 8).ok()?
+            }
+            ModeValue::Integer(mode) => *mode,
+        };
+        (mode <= 0o777).then_some(mode)
+    }
+}
+
+impl<'de> Deserialize<'de> for PermissionMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PermissionVisitor;
+
+        impl<'de> Visitor<'de> for PermissionVisitor {
+            type Value = PermissionMap;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a table of glob = \"0644\"")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut permissions = PermissionMap::default();
+                while let Some((pattern, value)) = map.next_entry::<String, ModeValue>()? {
+                    let mode = value.mode().ok_or_else(|| de::Error::custom(tr!(config_bad_mode, pattern)))?;
+                    permissions
+                        .insert(&pattern, mode)
+                        .map_err(|e| de::Error::custom(tr!(config_bad_pattern, pattern, e)))?;
+                }
+                Ok(permissions)
+            }
+        }
+
+        deserializer.deserialize_map(PermissionVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(text: &str) -> toml::Table {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn merge_overrides_keys_and_keeps_siblings() {
+        let mut lower = table("[build]\nbuild_dir = \"a\"\ncompression_level = 6\n[version_code]\nstrategy = \"env\"\nenv = \"RUN\"\n");
+        merge(&mut lower, table("[build]\ncompression_level = 9\n[version_code]\nstrategy = \"keep\"\n"));
+        assert_eq!(lower["build"]["build_dir"].as_str(), Some("a"));
+        assert_eq!(lower["build"]["compression_level"].as_integer(), Some(9));
+        // [version_code] 整体替换，不会留下上一层的 env
+        assert!(lower["version_code"].get("env").is_none());
+    }
+
+    #[test]
+    fn cli_overrides_project_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".ksmm")).unwrap();
+        fs::write(dir.path().join(CONFIG_FILE), "[build]\ncompression_level = 3\nfollow_symlinks = true\n").unwrap();
+
+        let config = Config::load(Some(dir.path()), &Overrides::default()).unwrap();
+        assert_eq!(config.build.compression_level, 3);
+
+        let mut cli = Overrides::default();
+        cli.set("build.compression_level", 7);
+        let config = Config::load(Some(dir.path()), &cli).unwrap();
+        assert_eq!(config.build.compression_level, 7);
+        assert!(config.build.follow_symlinks);
+    }
+
+    #[test]
+    fn invalid_layers_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".ksmm")).unwrap();
+        fs::write(dir.path().join(CONFIG_FILE), "[build]\nunknown_key = 1\n").unwrap();
+        assert!(matches!(Config::load(Some(dir.path()), &Overrides::default()), Err(KsmmError::Config(_))));
+
+        let mut cli = Overrides::default();
+        cli.set("build.compression_level", 10);
+        assert!(matches!(Config::load(None, &cli), Err(KsmmError::Config(_))));
+    }
+
+    #[test]
+    fn permission_map_accepts_strings_and_octal() {
+        let config: Config = toml::from_str("[permissions]\n\"system/bin/*\" = \"0755\"\n\"system/bin/keep\" = 0o700\n\"data/\" = \"0750\"\n").unwrap();
+        let permissions = &config.permissions;
+        assert_eq!(permissions.mode_for("system/bin/tool", false), Some(0o755));
+        // 靠后的模式优先
+        assert_eq!(permissions.mode_for("system/bin/keep", false), Some(0o700));
+        assert_eq!(permissions.mode_for("data", true), Some(0o750));
+        assert_eq!(permissions.mode_for("data", false), None);
+        assert_eq!(permissions.mode_for("system/etc/a", false), None);
+    }
+
+    #[test]
+    fn permission_map_rejects_bad_modes() {
+        assert!(toml::from_str::<Config>("[permissions]\n\"a\" = \"0999\"\n").is_err());
+        assert!(toml::from_str::<Config>("[permissions]\n\"a\" = \"rwx\"\n").is_err());
+    }
+}
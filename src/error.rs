@@ -0,0 +1,88 @@
+use std::fmt;
+use std::io;
+use std::process::ExitCode;
+use crate::i18n::tr;
+use crate::module_prop::PropError;
+
+/// 所有命令共用的错误类型
+///
+/// 每个分类对应一个固定的退出码，CI 可以据此区分失败原因。
+#[derive(Debug)]
+pub enum KsmmError {
+    /// 当前目录不是模块目录
+    ModulePropMissing,
+    /// module.prop 中的字段缺失或格式错误
+    ModuleProp(PropError),
+    /// 读写文件失败，`message` 中已包含出错的操作
+    Io { message: String, source: io::Error },
+    /// build.conf / .gitignore 中的模式无效
+    Pattern(String),
+    /// 签名或密钥操作失败
+    Sign(String),
+    /// git 命令执行失败
+    Git(String),
+    /// 打包或读取 zip 失败
+    Zip(String),
+    /// 模板无效
+    Template(String),
+    /// 参数、答案文件或交互输入无效
+    Input(String),
+    /// `ksmm check` 发现了错误
+    Check(String),
+    /// .ksmm/ksmm.toml 无效
+    Config(String),
+    /// 项目中的内容无法打包，例如指向项目之外的符号链接
+    Build(String),
+}
+
+impl KsmmError {
+    pub fn io(message: String, source: io::Error) -> Self {
+        KsmmError::Io { message, source }
+    }
+
+    /// 进程退出码，2 与 clap 的参数错误保持一致
+    pub fn exit_code(&self) -> ExitCode {
+        let code = match self {
+            KsmmError::Input(_) => 2,
+            KsmmError::ModulePropMissing | KsmmError::ModuleProp(_) => 3,
+            KsmmError::Io { .. } => 4,
+            KsmmError::Pattern(_) => 5,
+            KsmmError::Sign(_) => 6,
+            KsmmError::Git(_) => 7,
+            KsmmError::Zip(_) => 8,
+            KsmmError::Template(_) => 9,
+            KsmmError::Check(_) => 10,
+            KsmmError::Config(_) => 11,
+            KsmmError::Build(_) => 12,
+        };
+        ExitCode::from(code)
+    }
+}
+
+impl fmt::Display for KsmmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KsmmError::ModulePropMissing => f.write_str(tr!(err_module_prop_missing)),
+            KsmmError::ModuleProp(e) => e.fmt(f),
+            KsmmError::Io { message, .. } => f.write_str(message),
+            KsmmError::Pattern(message)
+            | KsmmError::Sign(message)
+            | KsmmError::Git(message)
+            | KsmmError::Zip(message)
+            | KsmmError::Template(message)
+            | KsmmError::Input(message)
+            | KsmmError::Check(message)
+            | KsmmError::Config(message)
+            | KsmmError::Build(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for KsmmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KsmmError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,796 @@
+use clap::ValueEnum;
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    /// 简体中文
+    #[value(name = "zh-CN", alias = "zh")]
+    ZhCn,
+    /// English
+    #[value(name = "en", alias = "en-US")]
+    En,
+}
+
+impl Lang {
+    /// 从 `zh_CN.UTF-8`、`en_US` 这样的 locale 推断语言
+    ///
+    /// `C`、`POSIX` 等没有语言信息的 locale 使用默认的简体中文。
+    fn from_locale(locale: &str) -> Option<Self> {
+        let locale = locale.to_ascii_lowercase();
+        if locale.starts_with("zh") {
+            Some(Lang::ZhCn)
+        } else if locale.is_empty() || locale == "c" || locale.starts_with("c.") || locale == "posix" {
+            None
+        } else {
+            Some(Lang::En)
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Lang::ZhCn => "zh-CN",
+            Lang::En => "en",
+        }
+    }
+
+    /// 按 `LC_ALL` > `LC_MESSAGES` > `LANG` 的顺序读取环境变量
+    fn detect() -> Self {
+        for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+            if let Ok(value) = env::var(var)
+                && !value.is_empty()
+            {
+                return Lang::from_locale(&value).unwrap_or(Lang::ZhCn);
+            }
+        }
+        Lang::ZhCn
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// 设置界面语言，未指定时根据环境变量检测；只有第一次调用生效
+pub fn init(lang: Option<Lang>) {
+    let _ = LANG.set(lang.unwrap_or_else(Lang::detect));
+}
+
+/// 在解析命令行之前从参数中找出 `--lang`，以便帮助信息也能使用对应语言
+pub fn lang_from_args(args: &[String]) -> Option<Lang> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = if arg == "--lang" {
+            iter.next().map(|s| s.as_str())
+        } else {
+            arg.strip_prefix("--lang=")
+        };
+        if let Some(value) = value {
+            return Lang::from_str(value, true).ok();
+        }
+    }
+    None
+}
+
+pub fn lang() -> Lang {
+    *LANG.get_or_init(Lang::detect)
+}
+
+/// 延迟到输出时才按当前语言取出的消息，用于静态表中的描述文本
+pub type Message = fn(&Catalog) -> &'static str;
+
+pub fn catalog() -> &'static Catalog {
+    match lang() {
+        Lang::ZhCn => &ZH_CN,
+        Lang::En => &EN,
+    }
+}
+
+/// 将参数依次填入消息中的 `{}`，`{0}`、`{1}` 可以按位置引用参数
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) if after[..end].chars().all(|c| c.is_ascii_digit()) => {
+                let index = if end == 0 {
+                    next += 1;
+                    next - 1
+                } else {
+                    after[..end].parse().unwrap_or(usize::MAX)
+                };
+                match args.get(index) {
+                    Some(arg) => result.push_str(&arg.to_string()),
+                    None => result.push_str(&rest[start..start + end + 2]),
+                }
+                rest = &after[end + 1..];
+            }
+            _ => {
+                result.push('{');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 取出当前语言的消息，带参数时返回填充后的 `String`
+#[macro_export]
+macro_rules! tr {
+    ($key:ident) => {
+        $crate::i18n::catalog().$key
+    };
+    ($key:ident, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::catalog().$key, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+pub use crate::tr;
+
+macro_rules! catalog {
+    ($($key:ident: $zh:literal, $en:literal;)*) => {
+        /// 消息目录，每种语言都必须提供全部消息
+        pub struct Catalog {
+            $(pub $key: &'static str,)*
+        }
+
+        pub const ZH_CN: Catalog = Catalog {
+            $($key: $zh,)*
+        };
+
+        pub const EN: Catalog = Catalog {
+            $($key: $en,)*
+        };
+    };
+}
+
+catalog! {
+    build_refresh_version_code: "刷新 versionCode: {}",
+        "Refreshed versionCode: {}";
+    build_create_dir: "创建目录: {}",
+        "Created directory: {}";
+    build_copy_file: "复制文件: {} -> {}",
+        "Copied file: {} -> {}";
+    build_include_file: "文件 '{}' 匹配包括模式 '{}' ({}), 包括",
+        "File '{}' matches include pattern '{}' ({}), included";
+    build_ignore_file: "文件 '{}' 匹配忽略模式 '{}' ({}), 忽略",
+        "File '{}' matches ignore pattern '{}' ({}), ignored";
+    build_created_release_file: "创建 {}",
+        "Created {}";
+    build_dry_run_start: "预演构建，不会修改 module.prop 与 .ksmm/",
+        "Dry run, module.prop and .ksmm/ will not be modified";
+    build_dry_run_tree: "将被打包的内容:",
+        "Contents of the module zip:";
+    build_dry_run_no_rule: "没有匹配的规则",
+        "no matching rule";
+    build_dry_run_summary: "共 {} 个文件，{} 个目录",
+        "{} files, {} directories";
+    build_dry_run_all_rules_matched: "所有规则都匹配了至少一个路径",
+        "Every rule matched at least one path";
+    build_dry_run_unmatched_rules: "以下规则没有匹配任何路径:",
+        "These rules matched nothing:";
+    build_bad_source_date_epoch: "SOURCE_DATE_EPOCH '{}' 不是有效的 Unix 时间戳",
+        "SOURCE_DATE_EPOCH '{}' is not a valid Unix timestamp";
+    build_reproducible_start: "重新构建并比较两次的结果...",
+        "Rebuilding to compare both results...";
+    build_reproducible_ok: "两次构建得到的 zip 完全相同",
+        "Both builds produced identical zips";
+    build_not_reproducible: "两次构建得到的 {} 不同",
+        "The two builds of {} differ";
+    build_not_reproducible_entry: "两次构建得到的 {} 不同，第一个不同的条目: {}",
+        "The two builds of {} differ, first differing entry: {}";
+    config_invalid: "{} 无效: {}",
+        "{} is invalid: {}";
+    config_source_env: "KSMM_* 环境变量",
+        "KSMM_* environment variables";
+    config_source_cli: "命令行参数",
+        "Command-line options";
+    config_source_merged: "合并后的配置",
+        "The merged configuration";
+    config_bad_env: "环境变量 {} 的值 '{}' 无效",
+        "Invalid value '{1}' for environment variable {0}";
+    config_bad_compression_level: "压缩级别 {} 无效，应在 0 到 9 之间",
+        "Invalid compression level {}: expected 0 to 9";
+    config_bad_output_dir: "{} '{}' 不能是项目根目录或其上级目录",
+        "{} '{}' must not be the project root or one of its parents";
+    config_output_dir_outside: "{} '{}' 必须位于项目目录中",
+        "{} '{}' must be inside the project directory";
+    config_output_dir_reserved: "{} '{}' 与 .ksmm/ 中 ksmm 自己的文件重叠，请使用 .ksmm/ 中的其他目录",
+        "{} '{}' overlaps files ksmm keeps in .ksmm/; use another directory inside .ksmm/";
+    config_output_dir_packaged: "{} '{}' 位于会被打包的目录中，请放在 .ksmm/ 中，或先在 .gitignore 或 .ksmm/build.conf 中忽略它",
+        "{} '{}' is inside the packaged tree; put it in .ksmm/ or ignore it in .gitignore or .ksmm/build.conf first";
+    config_same_output_dir: "构建目录 {} 与发布目录 {} 不能相同或互相包含",
+        "The build directory {} and the release directory {} must not be the same or nested";
+    config_bad_variant_name: "变体名 '{}' 无效，只能包含字母、数字、-、_ 与 .",
+        "Invalid variant name '{}': only letters, digits, -, _ and . are allowed";
+    config_variant_version_code: "变体 {} 不能覆盖 versionCode，它由 [version_code] 统一生成",
+        "Variant {} cannot override versionCode; it is generated by [version_code] for all variants";
+    variant_bad_id: "变体 {} 的模块 ID '{}' 无效，必须以字母开头，只能包含字母、数字、点、下划线和连字符",
+        "Module ID '{1}' of variant {0} is invalid: it must start with a letter and contain only letters, digits, dots, underscores and hyphens";
+    variant_unknown: "未知的变体 {}，已配置的变体: {}",
+        "Unknown variant {}; configured variants: {}";
+    variant_none_configured: "没有配置任何变体，请在 .ksmm/ksmm.toml 中添加 [variants.<name>]",
+        "No variants configured; add [variants.<name>] to .ksmm/ksmm.toml";
+    variant_overlay_missing: "变体 {} 的覆盖目录 {} 不存在",
+        "Overlay directory {1} of variant {0} does not exist";
+    variant_artifact_collision: "多个变体的发布文件名都是 {}，请在 build.artifact_name 中加入 {variant} 或为变体设置 id_suffix",
+        "Several variants would produce {}; add {variant} to build.artifact_name or set id_suffix for the variants";
+    abi_duplicate_dir: "架构 {} 同时有 bin/{}/ 与 bin/{}/，请只保留一个",
+        "ABI {} has both bin/{}/ and bin/{}/; keep only one";
+    abi_missing: "[abi] 中声明了架构 {0}，但 {1}/{0}/ 不存在或其中没有文件",
+        "ABI {0} is declared in [abi], but {1}/{0}/ is missing or empty";
+    abi_missing_binary: "架构 {0} 缺少可执行文件 {1}（应位于 {2}/{3}/{1}），其他架构提供了它",
+        "ABI {0} is missing {1} (expected at {2}/{3}/{1}), which other ABIs provide";
+    config_native_no_targets: "[native] 中配置了包但没有 targets，请列出 Rust 目标三元组，例如 aarch64-linux-android",
+        "[native] lists packages but no targets; list Rust target triples such as aarch64-linux-android";
+    native_unknown_package: "{1} 中没有名为 {0} 的包",
+        "There is no package named {0} in {1}";
+    native_no_binaries: "包 {} 没有可执行目标",
+        "Package {} has no binary targets";
+    native_dest_collision: "多个目标的可执行文件都会放到 {}，请在 native.dest（{}）中加入 {abi} 或 {target}",
+        "Binaries of several targets would be placed at {}; add {abi} or {target} to native.dest ({})";
+    native_bad_dest: "native.dest 模板 '{}' 无效: {}",
+        "Invalid native.dest template '{}': {}";
+    native_unknown_abi: "无法确定目标 {} 对应的设备架构，请改用 {target}",
+        "cannot determine the device architecture of target {}; use {target} instead";
+    native_dest_outside: "路径必须是模块中的相对路径",
+        "the path must be relative and inside the module";
+    native_exec_failed: "无法执行 cargo: {}",
+        "Failed to run cargo: {}";
+    native_metadata_failed: "读取 {} 的 cargo metadata 失败: {}",
+        "Failed to read cargo metadata of {}: {}";
+    native_build_failed: "cargo 构建目标 {} 失败（{}），模块构建已中止",
+        "cargo build for target {} failed ({}); module build aborted";
+    native_binary_missing: "cargo 构建完成，但没有生成 {}",
+        "cargo build finished but did not produce {}";
+    config_bad_mode: "'{}' 的权限无效，应为 \"0644\" 这样的八进制字符串或 0o644",
+        "Invalid mode for '{}', expected an octal string such as \"0644\" or 0o644";
+    config_bad_pattern: "模式 '{}' 无效: {}",
+        "Invalid pattern '{}': {}";
+    build_symlink_dangling: "符号链接 '{}' 指向不存在的 '{}'",
+        "Symlink '{}' points to '{}', which does not exist";
+    build_symlink_escapes: "符号链接 '{}' 指向项目目录之外的 '{}'，可以在 .ksmm/ksmm.toml 中设置 [build] follow_symlinks = true 复制其内容",
+        "Symlink '{}' points to '{}' outside the project; set [build] follow_symlinks = true in .ksmm/ksmm.toml to copy its contents instead";
+    build_symlink_not_packaged: "符号链接 '{}' 指向的 '{}' 不会被打包，安装后链接会失效",
+        "Symlink '{}' points to '{}', which is not packaged; the link would dangle after installation";
+    build_symlink_loop: "跟随符号链接 '{}' 会形成循环",
+        "Following symlink '{}' creates a loop";
+    build_create_symlink: "创建符号链接: {} -> {}",
+        "Created symlink: {} -> {}";
+    version_code_bad_semver: "无法从 version '{}' 计算 versionCode，需要 1.2.3 这样的版本，且次版本号与修订号不超过 99",
+        "Cannot derive versionCode from version '{}': expected something like 1.2.3 with minor and patch at most 99";
+    version_code_env_missing: "环境变量 {} 未设置，无法生成 versionCode",
+        "Environment variable {} is not set, cannot derive versionCode";
+    version_code_env_invalid: "环境变量 {} 的值 '{}' 不是整数",
+        "Environment variable {} is '{}', which is not an integer";
+    version_code_no_commits: "不在 git 仓库中或还没有提交，无法按提交数生成 versionCode",
+        "Not in a git repository or no commits yet, cannot use the commit count as versionCode";
+    version_code_out_of_range: "versionCode {} 超出 Android 允许的范围（不能大于 2147483647）",
+        "versionCode {} is out of range for Android (must not exceed 2147483647)";
+    version_code_lower: "按 version_code.strategy 生成的 versionCode {} 小于 module.prop 中的 {}，已安装的设备不会把新版本识别为更新；请换回原来的策略，或用 `ksmm bump <版本> --version-code <CODE>` 明确重设 versionCode（已安装旧版本的设备需要先卸载）",
+        "The versionCode {} from version_code.strategy is lower than {} in module.prop, so devices would not see the new build as an update; switch back to the previous strategy, or reset versionCode explicitly with `ksmm bump <VERSION> --version-code <CODE>` (devices with the old build installed must uninstall it first)";
+    build_version_code_no_write: "本次构建的 versionCode: {}（不写回 module.prop）",
+        "versionCode for this build: {} (module.prop left unchanged)";
+    bump_not_semver: "version '{}' 不是语义化版本，需要 1.2.3 或 v1.2.3-beta.1 这样的版本",
+        "version '{}' is not a semantic version: expected something like 1.2.3 or v1.2.3-beta.1";
+    bump_not_newer: "新版本 {} 必须高于当前版本 {}",
+        "The new version {} must be higher than the current version {}";
+    git_command_failed: "git {} 失败: {}",
+        "git {} failed: {}";
+    bump_bad_target: "无效的版本号 '{}'，需要 major、minor、patch、prerelease 或 1.2.3 这样的版本",
+        "Invalid version '{}': expected major, minor, patch, prerelease or something like 1.2.3";
+    bump_tag_exists: "标签 {} 已存在",
+        "Tag {} already exists";
+    bump_version: "版本号: {} → {}",
+        "Version: {} → {}";
+    bump_version_code_reset: "versionCode: {} → {}（已安装旧版本的设备需要先卸载才能更新）",
+        "versionCode: {} → {} (devices with the old build installed must uninstall it before updating)";
+    bump_changelog_added: "{} 中添加了 {} 段落",
+        "Added a {1} section to {0}";
+    bump_changelog_exists: "{} 中已有 {} 段落，保持不变",
+        "{} already has a {} section, left unchanged";
+    bump_tagged: "已提交 module.prop 与更新日志并创建标签 {}",
+        "Committed module.prop and the changelog and created tag {}";
+    bump_push_hint: "推送提交与标签以触发发布工作流: git push --follow-tags",
+        "Push the commit and tag to trigger the release workflow: git push --follow-tags";
+    changelog_start: "生成更新日志...",
+        "Generating changelog...";
+    changelog_range: "提交范围 {}，共 {} 条需要记录",
+        "Commit range {}: {} entries to record";
+    changelog_nothing: "上一个版本以来没有需要记录的提交",
+        "No commits worth recording since the last version";
+    changelog_written: "已写入 {} 中的 {} 段落",
+        "Wrote the {1} section of {0}";
+    changelog_kept: "{} 中的 {} 段落已有内容，保持不变",
+        "The {1} section of {0} already has content, left unchanged";
+    changelog_bump_hint: "发布新版本请使用 ksmm bump，新段落会自动填入这些提交",
+        "To release a new version use ksmm bump, which fills the new section with these commits";
+    changelog_title: "更新日志",
+        "Changelog";
+    changelog_breaking: "破坏性变更",
+        "Breaking changes";
+    changelog_features: "新功能",
+        "Features";
+    changelog_fixes: "问题修复",
+        "Bug fixes";
+    changelog_performance: "性能优化",
+        "Performance";
+    changelog_reverts: "回退",
+        "Reverts";
+    changelog_other: "其他更改",
+        "Other changes";
+    update_json_unclosed: "update.json 模板 '{}' 中的占位符没有闭合的 }",
+        "update.json template '{}' has a placeholder without a closing }";
+    update_json_missing_value: "update.json 模板 '{}' 中的 {} 无法确定，请在 .ksmm/ksmm.toml 的 [update_json] 中设置 repository 或 base_url",
+        "Cannot resolve {1} in update.json template '{0}': set repository or base_url under [update_json] in .ksmm/ksmm.toml";
+    update_json_unknown_placeholder: "update.json 模板 '{}' 中有未知的占位符 {}，可用的占位符: {}",
+        "update.json template '{}' uses unknown placeholder {}; available placeholders: {}";
+    update_json_reserved_field: "[update_json.extra] 中的 {} 是 update.json 的固定字段，不能覆盖",
+        "{} in [update_json.extra] is a built-in update.json field and cannot be overridden";
+    build_bad_artifact_name: "发布文件名模板 '{}' 无效: {}",
+        "Invalid artifact name template '{}': {}";
+    build_artifact_name_unclosed: "占位符没有闭合的 }",
+        "a placeholder has no closing }";
+    build_artifact_name_unknown: "未知的占位符 {}，可用的占位符: {}",
+        "unknown placeholder {}; available placeholders: {}";
+    build_artifact_name_path: "生成的文件名为空或包含路径分隔符",
+        "the resulting file name is empty or contains a path separator";
+    build_variant_start: "变体 {}",
+        "Variant {}";
+    build_dry_run_overlay: "覆盖自 {}",
+        "from overlay {}";
+    build_dry_run_abi: "安装时按设备架构把 bin/<abi>/ 中的文件复制到 system/bin: {}",
+        "On install, bin/<abi>/ for the device architecture is copied into system/bin: {}";
+    build_dry_run_native: "由 cargo 构建: {} --target {}",
+        "built by cargo: {} --target {}";
+    build_clean_unmarked: "{} 不是由 ksmm 创建的（没有 {}），为避免删除其中的文件，构建已中止；请确认后手动清空或换一个目录",
+        "{} was not created by ksmm (no {}); the build was stopped to avoid deleting its files. Empty it manually or choose another directory";
+    build_clean_build_dir: "清空 build 目录",
+        "Cleaned build directory";
+    build_clean_release_dir: "清空 release 目录",
+        "Cleaned release directory";
+    build_sign_check: "开始检查签名",
+        "Checking for signing keys";
+    build_sign_key_found: "检测到PEM密钥文件",
+        "Found PEM key file";
+    build_sign_no_key: "未检测到PEM密钥文件，跳过签名",
+        "No PEM key file found, skipping signing";
+    build_sign_success: "签名成功",
+        "Signed successfully";
+    build_signed_file_missing: "签名完成，但未找到签名文件: {}",
+        "Signing finished but the signed file was not found: {}";
+    build_git_failed: "获取 git 提交信息失败: {}",
+        "Failed to read git commit: {}";
+    build_bad_pattern: "{}:{}: 无效的模式 '{}': {}",
+        "{}:{}: invalid pattern '{}': {}";
+    read_file_failed: "读取 {} 失败: {}",
+        "Failed to read {}: {}";
+    write_file_failed: "写入 {} 失败: {}",
+        "Failed to write {}: {}";
+    prop_missing_key: "缺少必需字段 {}",
+        "missing required field {}";
+    prop_empty_key: "字段 {} 为空",
+        "field {} is empty";
+    prop_invalid_id: "id '{}' 无效，必须以字母开头，只能包含字母、数字、点、下划线和连字符",
+        "invalid id '{}': it must start with a letter and contain only letters, digits, dots, underscores and hyphens";
+    prop_version_code_out_of_range: "versionCode {} 超出 Android 允许的范围（不能大于 2147483647）",
+        "versionCode {} is out of range for Android (must not exceed 2147483647)";
+    prop_invalid_version_code: "versionCode '{}' 不是整数",
+        "versionCode '{}' is not an integer";
+    prop_invalid_update_json: "updateJson '{}' 不是有效的 http(s) 地址",
+        "updateJson '{}' is not a valid http(s) URL";
+    prop_malformed_line: "无法解析的行 '{}'，应为 key=value",
+        "cannot parse line '{}', expected key=value";
+    prop_duplicate_key: "字段 {} 重复，第一次出现在第 {} 行",
+        "duplicate field {}, first defined on line {}";
+    build_start: "构建模块...",
+        "Building module...";
+    err_module_prop_missing: "未找到 module.prop 文件，请确保在模块目录中运行此命令",
+        "module.prop not found, make sure to run this command in a module directory";
+    build_clean_failed: "清空目录失败: {}",
+        "Failed to clean directories: {}";
+    build_create_release_dir_failed: "创建 release 目录失败: {}",
+        "Failed to create release directory: {}";
+    build_update_json_failed: "生成 update.json 失败: {}",
+        "Failed to generate update.json: {}";
+    build_copy_failed: "复制文件到构建目录失败: {}",
+        "Failed to copy files to the build directory: {}";
+    build_abi_installer: "在 customize.sh 中加入按架构安装的代码: {}",
+        "Added per-ABI install code to customize.sh: {}";
+    build_native_start: "cargo 构建 {}",
+        "cargo build for {}";
+    build_native_done: "cargo 构建完成",
+        "cargo build finished";
+    build_done: "模块构建完成!",
+        "Module built!";
+    build_zip_failed: "打包ZIP失败: {}",
+        "Failed to package ZIP: {}";
+    sign_start: "对文件进行签名",
+        "Signing file";
+    sign_file_missing: "文件 '{}' 不存在",
+        "File '{}' does not exist";
+    sign_configured_key_missing: "配置的签名密钥不存在: {}",
+        "Configured signing key not found: {}";
+    sign_replace_failed: "用签名后的文件替换 {} 失败: {}",
+        "Failed to replace {} with the signed file: {}";
+    sign_key_dir_missing: "未找到密钥目录，请先使用 'ksmm key new <name>' 创建密钥",
+        "Key directory not found, create a key with 'ksmm key new <name>' first";
+    sign_key_dir_hint: "或者手动将 ED25519 类型的 .pem 文件放置在 .ksmm/key/ 目录中",
+        "or place an ED25519 .pem file in .ksmm/key/ manually";
+    sign_read_key_dir_failed: "读取密钥目录失败: {}",
+        "Failed to read key directory: {}";
+    sign_no_pem: "未找到任何 .pem 密钥文件，请先使用 'ksmm key new <name>' 创建密钥",
+        "No .pem key file found, create a key with 'ksmm key new <name>' first";
+    sign_using_key: "使用密钥: {}",
+        "Using key: {}";
+    sign_zakosign_failed: "获取 zakosign 失败: {}",
+        "Failed to prepare zakosign: {}";
+    sign_exec_failed: "执行签名命令失败: {}",
+        "Failed to run the signing command: {}";
+    sign_success: "文件签名成功",
+        "File signed successfully";
+    sign_input_file: "输入文件: {}",
+        "Input file: {}";
+    sign_output_is_input: "签名后的文件 '{}' 与输入文件相同，拒绝覆盖",
+        "The signed file '{}' would overwrite the input file, refusing to sign";
+    sign_output_file: "输出文件: {}",
+        "Output file: {}";
+    sign_failed: "签名失败",
+        "Signing failed";
+    sign_stderr: "错误信息: {}",
+        "Error output: {}";
+    key_new_start: "创建新的签名密钥",
+        "Creating a new signing key";
+    key_create_dir_failed: "创建密钥目录失败: {}",
+        "Failed to create key directory: {}";
+    key_exists: "密钥文件 '{}' 已存在",
+        "Key file '{}' already exists";
+    key_exec_failed: "执行密钥创建命令失败: {}",
+        "Failed to run the key creation command: {}";
+    key_created: "密钥已创建: {}",
+        "Key created: {}";
+    key_private_file: "私钥文件: {}",
+        "Private key file: {}";
+    key_failed: "密钥创建失败",
+        "Key creation failed";
+    key_stdout: "标准输出: {}",
+        "Standard output: {}";
+    template_minimal: "最小模块：module.prop、安装脚本与 CI 工作流",
+        "Minimal module: module.prop, install scripts and CI workflow";
+    template_webui: "带 WebUI 页面与执行按钮的模块",
+        "Module with a WebUI page and an action button";
+    template_action_only: "只带执行按钮 action.sh 的模块",
+        "Module with only an action button (action.sh)";
+    template_binary: "向 /system/bin 提供命令行工具的模块",
+        "Module that ships a command-line tool in /system/bin";
+    template_invalid_bool: "{}: '{}' 不是有效的布尔值",
+        "{}: '{}' is not a valid boolean";
+    template_invalid_choice: "{}: '{}' 不在可选值 {} 中",
+        "{}: '{}' is not one of {}";
+    template_invalid_text: "{}: '{}' 不匹配 {}",
+        "{}: '{}' does not match {}";
+    template_bad_key: "问题的 key '{}' 只能包含字母、数字和下划线",
+        "Prompt key '{}' may only contain letters, digits and underscores";
+    template_duplicate_key: "问题的 key '{}' 重复",
+        "Duplicate prompt key '{}'";
+    template_bad_validate: "{}: 无效的 validate 正则: {}",
+        "{}: invalid validate regex: {}";
+    template_choice_without_choices: "{}: choice 类型的问题必须提供 choices",
+        "{}: choice prompts must provide choices";
+    template_bad_condition: "无效的条件表达式 '{}'",
+        "Invalid condition '{}'";
+    template_not_found: "未找到模板 '{}'，可用的内置模板: {}",
+        "Template '{}' not found, built-in templates: {}";
+    template_read_dir_failed: "读取模板目录 {} 失败: {}",
+        "Failed to read template directory {}: {}";
+    init_created_dir: "创建 {} 目录",
+        "Created directory {}";
+    init_exists_skip: "{} 已存在，跳过创建",
+        "{} already exists, skipped";
+    init_created_file: "创建 {}",
+        "Created {}";
+    convert_api_magisk_cmd: "调用了 magisk 命令，KernelSU 中不可用",
+        "Calls the magisk command, which is not available on KernelSU";
+    convert_api_magisk_tmp: "使用了 Magisk 内部路径，KernelSU 中不存在",
+        "Uses Magisk internal paths that do not exist on KernelSU";
+    convert_api_magisk_data: "使用了 Magisk 数据目录，KernelSU 对应 /data/adb/ksu",
+        "Uses the Magisk data directory; the KernelSU equivalent is /data/adb/ksu";
+    convert_api_magiskpolicy: "使用了 magiskpolicy，请改用 sepolicy.rule 或 ksud sepolicy",
+        "Uses magiskpolicy; use sepolicy.rule or ksud sepolicy instead";
+    convert_api_magisk_ver: "KernelSU 中 MAGISK_VER 只是兼容值，请用 $KSU / $KSU_VER 判断环境",
+        "MAGISK_VER is only a compatibility value on KernelSU; check $KSU / $KSU_VER instead";
+    convert_api_util_functions: "引用了 Magisk 的 util_functions.sh，KernelSU 安装时会自动提供这些函数",
+        "Sources Magisk's util_functions.sh; KernelSU provides these functions during installation";
+    convert_api_mountpath: "使用了 Magisk 旧版镜像挂载接口，请改用 $MODPATH",
+        "Uses the legacy Magisk image mount API; use $MODPATH instead";
+    convert_api_zygisk: "依赖 Zygisk，KernelSU 需要额外安装 Zygisk Next 等实现",
+        "Depends on Zygisk; KernelSU needs an implementation such as Zygisk Next";
+    convert_api_riru: "依赖 Riru，KernelSU 中不可用",
+        "Depends on Riru, which is not available on KernelSU";
+    convert_custom_update_binary: "update-binary 不是 Magisk 的标准安装器，其中的自定义安装逻辑需要手动迁移到 customize.sh",
+        "update-binary is not the standard Magisk installer; migrate its custom logic to customize.sh manually";
+    convert_merge_installer: "已存在 customize.sh，{} 需要手动合并",
+        "customize.sh already exists, merge {} manually";
+    convert_flag_disabled: "{} 在旧模板中被 {}=false 禁用，未移动到模块根目录",
+        "{} is disabled by {}=false in the old template and was not moved to the module root";
+    convert_merge_common: "模块根目录已存在 {}，{} 需要手动合并",
+        "{} already exists in the module root, merge {} manually";
+    convert_removed: "移除 {} (备份到 {})",
+        "Removed {} (backed up to {})";
+    convert_moved: "移动 {} -> {}",
+        "Moved {} -> {}";
+    convert_will_remove: "将移除 {}",
+        "Would remove {}";
+    convert_will_move: "将移动 {} -> {}",
+        "Would move {} -> {}";
+    convert_will_create: "将创建 {}",
+        "Would create {}";
+    convert_magisk_prop: "{} 只对 Magisk 有意义，KernelSU 会忽略",
+        "{} is only meaningful to Magisk and is ignored by KernelSU";
+    convert_start: "转换 Magisk 模块为 KernelSU 模块...",
+        "Converting Magisk module to KernelSU layout...";
+    convert_analyze_failed: "分析模块结构失败: {}",
+        "Failed to analyze module layout: {}";
+    convert_nothing: "未检测到需要转换的 Magisk 安装器结构",
+        "No Magisk installer layout to convert";
+    convert_detected: "检测到 Magisk 模块结构",
+        "Detected Magisk module layout";
+    convert_scan_failed: "扫描脚本失败: {}",
+        "Failed to scan scripts: {}";
+    convert_failed: "转换失败: {}",
+        "Conversion failed: {}";
+    init_project_files_failed: "创建项目文件失败: {}",
+        "Failed to create project files: {}";
+    convert_findings: "发现 {} 处 Magisk 专用接口，需要手动处理:",
+        "Found {} uses of Magisk-only APIs that need manual attention:";
+    convert_dry_run: "预览模式，未修改任何文件",
+        "Dry run, no files were modified";
+    convert_done: "转换完成!",
+        "Conversion finished!";
+    convert_next: "下一步: 运行 {} 构建模块",
+        "Next: run {} to build the module";
+    git_status_clean: "工作目录清洁",
+        "clean";
+    git_status_dirty: "工作目录有变更",
+        "uncommitted changes";
+    gitignore_read_failed: "无法读取 .gitignore: {}",
+        "Cannot read .gitignore: {}";
+    gitignore_up_to_date: ".gitignore 已包含 .ksmm/ 的忽略规则，跳过添加",
+        ".gitignore already contains the .ksmm/ rules, skipped";
+    gitignore_updated: "更新 .gitignore",
+        "Updated .gitignore";
+    gitignore_write_failed: "无法写入 .gitignore: {}",
+        "Cannot write .gitignore: {}";
+    init_bad_set: "--set 参数 '{}' 应为 KEY=VALUE 格式",
+        "--set argument '{}' must be KEY=VALUE";
+    init_no_terminal: "{} (没有可交互的终端，请通过参数或答案文件提供)",
+        "{} (no interactive terminal, provide it via flags or an answers file)";
+    import_no_module_prop: "zip 根目录中没有 module.prop，不是有效的 KernelSU 模块",
+        "No module.prop in the zip root, not a valid KernelSU module";
+    import_symlink_escapes: "zip 中的符号链接 '{}' 指向模块目录之外的 '{}'，拒绝导入",
+        "Symlink '{}' in the zip points outside the module directory ('{}'), refusing to import";
+    import_entry_through_symlink: "zip 条目 '{}' 位于同一个 zip 中的符号链接 '{}' 之下，拒绝导入",
+        "Zip entry '{}' lies under the symlink '{}' from the same zip, refusing to import";
+    import_zip_slip: "zip 条目 '{}' 指向模块目录之外，拒绝导入",
+        "Zip entry '{}' points outside the module directory, refusing to import";
+    import_extracted: "解压 {}",
+        "Extracted {}";
+    import_start: "从 zip 导入模块: {}",
+        "Importing module from zip: {}";
+    import_open_failed: "打开 zip 失败: {}",
+        "Failed to open zip: {}";
+    init_prompt_path: "请输入创建地址",
+        "Project directory";
+    init_read_path_failed: "读取创建地址失败: {}",
+        "Failed to read project directory: {}";
+    import_dir_not_empty: "目录 '{}' 不为空，请选择一个新目录导入",
+        "Directory '{}' is not empty, choose a new directory to import into";
+    init_create_dir_failed: "创建项目目录失败: {}",
+        "Failed to create project directory: {}";
+    import_extract_failed: "解压失败: {}",
+        "Extraction failed: {}";
+    import_build_conf_comment: "从模块 zip 导入的文件",
+        "Files imported from the module zip";
+    import_build_conf_failed: "更新 .ksmm/build.conf 失败: {}",
+        "Failed to update .ksmm/build.conf: {}";
+    import_done: "模块导入完成!",
+        "Module imported!";
+    init_summary_path: "项目路径: {}",
+        "Project path: {}";
+    init_summary_id: "项目ID: {}",
+        "Project ID: {}";
+    import_next: "下一步: 在项目目录中运行 {} 重新构建模块",
+        "Next: run {} in the project directory to rebuild the module";
+    templates_builtin: "内置模板:",
+        "Built-in templates:";
+    templates_local_hint: "也可以通过 --template <目录> 使用本地模板",
+        "Local templates can be used with --template <dir>";
+    init_start: "初始化 KernelSU 模块...",
+        "Initializing KernelSU module...";
+    init_answers_failed: "读取答案文件失败: {}",
+        "Failed to read answers file: {}";
+    init_dir_name_invalid: "当前目录名称 '{}' 不符合模块ID格式要求。",
+        "Current directory name '{}' is not a valid module ID.";
+    init_prompt_id: "请输入项目名称 (必须以字母开头，只能包含字母、数字、点、下划线和连字符)",
+        "Module ID (must start with a letter and contain only letters, digits, dots, underscores and hyphens)";
+    init_read_id_failed: "读取项目名称失败: {}",
+        "Failed to read module ID: {}";
+    init_invalid_id: "模块ID格式无效。必须以字母开头，只能包含字母、数字、点、下划线和连字符。",
+        "Invalid module ID. It must start with a letter and contain only letters, digits, dots, underscores and hyphens.";
+    git_detected: "检测到 Git 仓库",
+        "Git repository detected";
+    git_branch: "分支",
+        "Branch";
+    git_remote: "远程仓库",
+        "Remote";
+    git_user: "用户名",
+        "User";
+    git_repo: "仓库",
+        "Repository";
+    git_status: "状态",
+        "Status";
+    init_config_ignored: "读取配置失败，init 使用默认值继续: {}",
+        "Failed to load the configuration, init continues with defaults: {}";
+    init_default_author: "无法获取git用户信息，使用默认作者: ksmm",
+        "Could not read git user, using default author: ksmm";
+    init_default_description: "一个用ksmm创建的{}模块",
+        "A {} module created with ksmm";
+    init_template_prompts_failed: "回答模板问题失败: {}",
+        "Failed to answer template prompts: {}";
+    init_action_exists: "action.sh 文件已存在，跳过执行按钮配置",
+        "action.sh already exists, skipped action button setup";
+    init_prompt_action: "是否需要执行按钮?",
+        "Add an action button?";
+    init_read_action_failed: "读取执行按钮配置失败: {}",
+        "Failed to read action button choice: {}";
+    init_webui_exists: "webroot 目录已存在，跳过WebUI配置",
+        "webroot already exists, skipped WebUI setup";
+    init_prompt_webui: "是否需要 WebUI?",
+        "Add a WebUI?";
+    init_read_webui_failed: "读取 WebUI 配置失败: {}",
+        "Failed to read WebUI choice: {}";
+    init_using_template: "使用模板: {}",
+        "Using template: {}";
+    init_render_failed: "渲染模板失败: {}",
+        "Failed to render template: {}";
+    init_done: "模块初始化完成!",
+        "Module initialized!";
+    init_next: "下一步:",
+        "Next steps:";
+    init_next_system: "编辑 {} 目录，添加你要修改的系统文件",
+        "Edit {} and add the system files you want to change";
+    init_next_customize: "根据需要修改 {} 安装脚本",
+        "Adjust the {} install script as needed";
+    init_next_build: "运行 {} 构建模块",
+        "Run {} to build the module";
+    init_success: "项目初始化成功!",
+        "Project initialized successfully!";
+    help_ksmm: "KernelSU Module Manager",
+        "KernelSU Module Manager";
+    help_version: "显示版本信息",
+        "Show version information";
+    help_lang: "界面语言 (默认根据 LANG 等环境变量检测)",
+        "Interface language (detected from LANG and related variables by default)";
+    help_init: "初始化模块",
+        "Initialize a module";
+    help_init_path: "创建地址",
+        "Project directory";
+    help_init_id: "模块 ID",
+        "Module ID";
+    help_init_name: "模块名称 (默认与 ID 相同)",
+        "Module name (defaults to the ID)";
+    help_init_author: "模块作者 (默认使用 git 用户名)",
+        "Module author (defaults to the git user name)";
+    help_init_description: "模块描述",
+        "Module description";
+    help_init_version: "模块版本 (默认 0.1.0)",
+        "Module version (defaults to 0.1.0)";
+    help_init_update_json: "updateJson 地址 (默认根据 git 远程仓库生成)",
+        "updateJson URL (derived from the git remote by default)";
+    help_init_action: "创建执行按钮脚本 action.sh",
+        "Create the action button script action.sh";
+    help_init_no_action: "不创建执行按钮脚本",
+        "Do not create an action button script";
+    help_init_webui: "创建 WebUI",
+        "Create a WebUI";
+    help_init_no_webui: "不创建 WebUI",
+        "Do not create a WebUI";
+    help_init_yes: "不进行交互，未指定的值全部使用默认值",
+        "Do not prompt, use defaults for everything not specified";
+    help_init_template: "使用的模板：内置模板名称或本地模板目录",
+        "Template to use: a built-in template name or a local template directory";
+    help_init_list_templates: "列出内置模板",
+        "List built-in templates";
+    help_init_from_zip: "从已发布的模块 zip 导入为 ksmm 项目",
+        "Import a released module zip as a ksmm project";
+    help_init_set: "回答模板清单中的问题，可重复使用",
+        "Answer a template manifest prompt, may be repeated";
+    help_init_answers: "从 TOML 答案文件读取上述选项",
+        "Read the options above from a TOML answers file";
+    help_build: "构建模块",
+        "Build the module";
+    help_convert: "将 Magisk 模块转换为 KernelSU 模块",
+        "Convert a Magisk module to a KernelSU module";
+    help_convert_path: "模块目录",
+        "Module directory";
+    help_convert_dry_run: "只报告需要修改的内容，不修改任何文件",
+        "Only report what would change, do not modify any files";
+    help_sign: "签名文件",
+        "Sign a file";
+    help_sign_file: "要签名的文件",
+        "File to sign";
+    help_key: "密钥管理",
+        "Manage signing keys";
+    help_key_new: "创建新的密钥对",
+        "Create a new key pair";
+    help_key_new_name: "密钥文件名",
+        "Key file name";
+    check_start: "检查模块...",
+        "Checking module...";
+    check_unknown_top_level: "KernelSU 不会使用模块根目录中的 {}，但它仍会被打包",
+        "KernelSU ignores {} in the module root, but it is still packaged";
+    check_unused_rule: "规则 '{}' 没有匹配任何文件",
+        "rule '{}' does not match any file";
+    check_script_bom: "脚本以 UTF-8 BOM 开头，设备上的 sh 无法识别",
+        "script starts with a UTF-8 BOM, which sh on the device cannot handle";
+    check_script_crlf: "脚本使用 CRLF 换行，请改为 LF",
+        "script uses CRLF line endings, use LF instead";
+    check_script_shebang: "脚本的第一行应为 {}",
+        "the first line of the script should be {}";
+    check_error: "错误",
+        "error";
+    check_warning: "警告",
+        "warning";
+    check_failed: "检查发现 {} 个错误，{} 个警告",
+        "check found {} errors and {} warnings";
+    check_passed: "检查通过，{} 个警告",
+        "check passed with {} warnings";
+    help_build_dry_run: "只输出将被打包的文件，不修改 module.prop 与 .ksmm/",
+        "Only print the files that would be packaged, without touching module.prop or .ksmm/";
+    help_build_explain: "说明每个路径由哪条规则决定，并列出没有匹配任何路径的规则",
+        "Show the rule that decided each path and list rules that matched nothing";
+    help_build_check_reproducible: "构建两次并确认得到字节相同的 zip",
+        "Build twice and verify the zips are byte-identical";
+    help_build_no_write: "只在打包的 module.prop 中使用新的 versionCode，不修改源文件",
+        "Use the new versionCode only in the packaged module.prop, leaving the source file untouched";
+    help_bump: "提升模块版本号",
+        "Bump the module version";
+    help_bump_target: "major、minor、patch、prerelease 或明确的版本号",
+        "major, minor, patch, prerelease or an explicit version";
+    help_bump_pre_id: "从正式版本进入预发布版本时使用的标识",
+        "Identifier used when a release moves to a prerelease";
+    help_bump_tag: "提交 module.prop 与 CHANGELOG.md 并创建 v* 标签，推送后触发发布工作流",
+        "Commit module.prop and CHANGELOG.md and create a v* tag that triggers the release workflow when pushed";
+    help_bump_version_code: "同时把 versionCode 重设为 CODE，即使小于现有的值，用于切换 version_code 策略",
+        "Also reset versionCode to CODE, even if it is lower than the current one, for switching version_code strategies";
+    help_changelog: "根据 git 历史生成更新日志",
+        "Generate the changelog from git history";
+    help_changelog_stdout: "只向标准输出打印生成的内容，不修改 CHANGELOG.md，可用作发布说明",
+        "Print the generated notes to stdout without touching CHANGELOG.md, e.g. for release notes";
+    help_build_build_dir: "构建目录 (覆盖配置中的 build.build_dir)",
+        "Build directory (overrides build.build_dir)";
+    help_build_release_dir: "发布目录 (覆盖配置中的 build.release_dir)",
+        "Release directory (overrides build.release_dir)";
+    help_build_artifact_name: "发布 zip 的文件名模板 (覆盖配置中的 build.artifact_name)",
+        "File name template of the release zip (overrides build.artifact_name)";
+    help_build_compression_level: "压缩级别 0-9，0 表示不压缩 (覆盖配置中的 build.compression_level)",
+        "Compression level 0-9, 0 stores files uncompressed (overrides build.compression_level)";
+    help_build_version_code: "versionCode 策略，env:NAME 表示读取环境变量 NAME (覆盖配置中的 version_code)",
+        "versionCode strategy, env:NAME reads the environment variable NAME (overrides version_code)";
+    help_build_key: "签名使用的私钥 (覆盖配置中的 sign.key)",
+        "Private key used for signing (overrides sign.key)";
+    help_sign_key: "使用的私钥 (覆盖配置中的 sign.key)",
+        "Private key to use (overrides sign.key)";
+    help_build_variant: "只构建指定的变体",
+        "Build only the given variant";
+    help_build_all_variants: "构建配置中的所有变体，每个变体生成一个 zip 与 update.json",
+        "Build every configured variant, producing one zip and update.json each";
+    help_check: "检查模块中的常见问题",
+        "Check the module for common problems";
+    help_check_path: "模块目录",
+        "Module directory";
+}
@@ -0,0 +1,359 @@
+use regex::Regex;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::error::KsmmError;
+use crate::i18n::tr;
+
+/// `.gitignore` / `.ksmm/build.conf` 中的一条规则，语法与 git 相同
+#[derive(Debug, Clone)]
+pub struct IgnoreRule {
+    /// 规则所在的文件，相对于项目根目录
+    pub source: PathBuf,
+    pub line: usize,
+    /// 原始模式，不含开头的 `!`
+    pub pattern: String,
+    /// 以 `!` 开头，重新包括之前被忽略的路径
+    pub negated: bool,
+    /// 以 `/` 结尾，只匹配目录
+    pub dir_only: bool,
+    /// 规则生效的目录，相对于项目根目录，根目录为空字符串
+    base: String,
+    /// 模式中含有 `/` 时相对于 `base` 匹配完整路径，否则只匹配文件名
+    anchored: bool,
+    regex: Regex,
+}
+
+impl IgnoreRule {
+    /// 解析一行规则，空行与注释返回 `None`
+    pub fn parse(source: &Path, line: usize, text: &str, base: &str) -> Result<Option<Self>, KsmmError> {
+        let text = trim_trailing_spaces(text);
+        if text.is_empty() || text.starts_with('#') {
+            return Ok(None);
+        }
+
+        let (negated, body) = match text.strip_prefix('!') {
+            Some(body) => (true, body),
+            None => (false, text),
+        };
+        let (dir_only, body) = match body.strip_suffix('/') {
+            Some(body) if !body.ends_with('\\') => (true, body),
+            _ => (false, body),
+        };
+        if body.is_empty() {
+            return Ok(None);
+        }
+
+        let anchored = body.contains('/');
+        let glob = body.strip_prefix('/').unwrap_or(body);
+        let regex = glob_regex(glob)
+            .map_err(|e| KsmmError::Pattern(tr!(build_bad_pattern, source.display(), line, text, e)))?;
+
+        Ok(Some(IgnoreRule {
+            source: source.to_path_buf(),
+            line,
+            pattern: text.strip_prefix('!').unwrap_or(text).to_string(),
+            negated,
+            dir_only,
+            base: base.to_string(),
+            anchored,
+            regex,
+        }))
+    }
+
+    /// 判断项目内的路径（以 `/` 分隔，不带 `./`）是否匹配本规则
+    pub fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        let path = if self.base.is_empty() {
+            path
+        } else {
+            match path.strip_prefix(&self.base).and_then(|rest| rest.strip_prefix('/')) {
+                Some(rest) => rest,
+                None => return false,
+            }
+        };
+
+        if self.anchored {
+            self.regex.is_match(path)
+        } else {
+            self.regex.is_match(path.rsplit('/').next().unwrap_or(path))
+        }
+    }
+
+    /// `source:line` 形式的位置
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.source.display(), self.line)
+    }
+}
+
+impl fmt::Display for IgnoreRule {
+    /// 规则在文件中的写法
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negated {
+            f.write_str("!")?;
+        }
+        f.write_str(&self.pattern)
+    }
+}
+
+/// 一组按出现顺序排列的规则，后出现的规则优先
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// 解析规则文件的内容，`source` 只用于错误信息与报告
+    pub fn parse(source: &Path, base: &str, content: &str) -> Result<Self, KsmmError> {
+        let mut rules = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            if let Some(rule) = IgnoreRule::parse(source, index + 1, line, base)? {
+                rules.push(rule);
+            }
+        }
+        Ok(IgnoreRules { rules })
+    }
+
+    /// 读取项目中的规则文件，`source` 相对于 `root`；文件不存在时没有规则
+    pub fn load(root: &Path, source: &Path, base: &str) -> Result<Self, KsmmError> {
+        let path = root.join(source);
+        match fs::read_to_string(&path) {
+            Ok(content) => Self::parse(source, base, &content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(KsmmError::io(tr!(read_file_failed, path.display(), e), e)),
+        }
+    }
+
+    pub fn rules(&self) -> &[IgnoreRule] {
+        &self.rules
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn extend(&mut self, other: IgnoreRules) {
+        self.rules.extend(other.rules);
+    }
+
+    /// 丢弃 `len` 之后的规则，用于离开子目录时移除其中 .gitignore 的规则
+    pub fn truncate(&mut self, len: usize) {
+        self.rules.truncate(len);
+    }
+
+    /// 返回决定该路径的规则，即最后一条匹配的规则
+    pub fn decide(&self, path: &str, is_dir: bool) -> Option<&IgnoreRule> {
+        self.rules.iter().rev().find(|rule| rule.matches(path, is_dir))
+    }
+}
+
+/// 去掉结尾未转义的空格
+fn trim_trailing_spaces(text: &str) -> &str {
+    let mut end = text.len();
+    while text[..end].ends_with(' ') {
+        let before = &text[..end - 1];
+        let backslashes = before.len() - before.trim_end_matches('\\').len();
+        if backslashes % 2 == 1 {
+            break;
+        }
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// 将 git 的通配符模式转换为正则表达式
+///
+/// `*` 与 `?` 不跨越 `/`；`**/`、`/**` 与 `/**/` 匹配任意层目录，其余位置的 `**` 与 `*` 相同；
+/// 支持 `[...]` 与 `[!...]` 字符类以及 `\` 转义。
+pub(crate) fn glob_regex(glob: &str) -> Result<Regex, regex::Error> {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex_pattern = String::from("^");
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                let at_start = i == 0 || chars[i - 1] == '/';
+                let at_end = i + 2 == chars.len() || chars[i + 2] == '/';
+                if at_start && at_end {
+                    if i + 2 == chars.len() {
+                        // 结尾的 `**` 匹配其中的所有内容
+                        regex_pattern.push_str(".*");
+                        i += 2;
+                    } else {
+                        // `**/` 匹配零或多层目录
+                        regex_pattern.push_str("(?:.*/)?");
+                        i += 3;
+                    }
+                } else {
+                    regex_pattern.push_str("[^/]*");
+                    i += 2;
+                }
+            }
+            '*' => {
+                regex_pattern.push_str("[^/]*");
+                i += 1;
+            }
+            '?' => {
+                regex_pattern.push_str("[^/]");
+                i += 1;
+            }
+            '[' => match char_class(&chars[i..]) {
+                Some((class, len)) => {
+                    regex_pattern.push_str(&class);
+                    i += len;
+                }
+                None => {
+                    regex_pattern.push_str(r"\[");
+                    i += 1;
+                }
+            },
+            '\\' if i + 1 < chars.len() => {
+                regex_pattern.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 2;
+            }
+            c => {
+                regex_pattern.push_str(&regex::escape(&c.to_string()));
+                i += 1;
+            }
+        }
+    }
+    regex_pattern.push('$');
+    Regex::new(&regex_pattern)
+}
+
+/// 转换以 `[` 开头的字符类，返回正则与消耗的字符数；没有闭合的 `]` 时返回 `None`
+fn char_class(chars: &[char]) -> Option<(String, usize)> {
+    let mut i = 1;
+    let mut class = String::from("[");
+    if matches!(chars.get(i), Some('!') | Some('^')) {
+        class.push_str("^/");
+        i += 1;
+    }
+
+    let start = i;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == ']' && i > start {
+            class.push(']');
+            return Some((class, i + 1));
+        }
+        match c {
+            '\\' if i + 1 < chars.len() => {
+                let escaped = chars[i + 1];
+                if escaped.is_ascii_punctuation() {
+                    class.push('\\');
+                }
+                class.push(escaped);
+                i += 2;
+                continue;
+            }
+            // 范围中的 `-` 保留原义，首尾的 `-` 是普通字符
+            '-' if i > start && chars.get(i + 1).is_some_and(|&next| next != ']') => class.push('-'),
+            c if c.is_ascii_punctuation() => {
+                class.push('\\');
+                class.push(c);
+            }
+            c => class.push(c),
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> IgnoreRules {
+        IgnoreRules::parse(Path::new(".gitignore"), "", content).unwrap()
+    }
+
+    fn ignored(rules: &IgnoreRules, path: &str, is_dir: bool) -> bool {
+        rules.decide(path, is_dir).is_some_and(|rule| !rule.negated)
+    }
+
+    #[test]
+    fn glob_regex_handles_stars() {
+        let regex = glob_regex("*.log").unwrap();
+        assert!(regex.is_match("a.log"));
+        assert!(!regex.is_match("dir/a.log"));
+
+        let regex = glob_regex("**/cache").unwrap();
+        assert!(regex.is_match("cache"));
+        assert!(regex.is_match("a/b/cache"));
+
+        let regex = glob_regex("docs/**").unwrap();
+        assert!(regex.is_match("docs/a/b.md"));
+        assert!(!regex.is_match("docs"));
+
+        let regex = glob_regex("a/**/b").unwrap();
+        assert!(regex.is_match("a/b"));
+        assert!(regex.is_match("a/x/y/b"));
+
+        let regex = glob_regex(r"\*.txt").unwrap();
+        assert!(regex.is_match("*.txt"));
+        assert!(!regex.is_match("a.txt"));
+    }
+
+    #[test]
+    fn glob_regex_handles_char_classes() {
+        let regex = glob_regex("file[0-9].txt").unwrap();
+        assert!(regex.is_match("file3.txt"));
+        assert!(!regex.is_match("filex.txt"));
+
+        let regex = glob_regex("[!a]*").unwrap();
+        assert!(regex.is_match("bcd"));
+        assert!(!regex.is_match("abc"));
+
+        let regex = glob_regex("[-.]x").unwrap();
+        assert!(regex.is_match("-x"));
+        assert!(regex.is_match(".x"));
+
+        // 没有闭合的 `[` 按普通字符处理
+        let regex = glob_regex("[abc").unwrap();
+        assert!(regex.is_match("[abc"));
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let rules = parse("*.log\n!keep.log\n");
+        assert!(ignored(&rules, "a.log", false));
+        assert!(!ignored(&rules, "keep.log", false));
+
+        let rules = parse("!keep.log\n*.log\n");
+        assert!(ignored(&rules, "keep.log", false));
+    }
+
+    #[test]
+    fn anchored_and_dir_only_rules() {
+        let rules = parse("/build/\ntmp\n# comment\n\nsrc/*.o\n");
+        assert_eq!(rules.len(), 3);
+        assert!(ignored(&rules, "build", true));
+        assert!(!ignored(&rules, "build", false));
+        assert!(!ignored(&rules, "sub/build", true));
+        assert!(ignored(&rules, "a/b/tmp", false));
+        assert!(ignored(&rules, "src/x.o", false));
+        assert!(!ignored(&rules, "lib/src/x.o", false));
+    }
+
+    #[test]
+    fn rules_apply_below_their_base() {
+        let rules = IgnoreRules::parse(Path::new("system/.gitignore"), "system", "*.bak\n").unwrap();
+        assert!(ignored(&rules, "system/etc/a.bak", false));
+        assert!(!ignored(&rules, "a.bak", false));
+    }
+
+    #[test]
+    fn trailing_spaces_are_trimmed_unless_escaped() {
+        assert_eq!(trim_trailing_spaces("a.txt  "), "a.txt");
+        assert_eq!(trim_trailing_spaces(r"a\ "), r"a\ ");
+    }
+}
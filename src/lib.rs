@@ -0,0 +1,50 @@
+//! ksmm：KernelSU 模块的构建与签名工具
+//!
+//! `ksmm` 命令行只是这些类型之上的一层薄封装，库本身不会向标准输出打印任何内容：
+//!
+//! ```no_run
+//! use ksmm::{Packager, Project, Signer};
+//!
+//! let project = Project::open("my_module")?;
+//! let prop = project.refresh_version_code()?;
+//! project.plan_build()?.execute(|_| {})?;
+//! let packager = Packager::new(&project);
+//! let zip = packager.package(&prop)?;
+//! if let Some(key) = project.signing_key()? {
+//!     Signer::new(key).sign_to(&zip, &packager.artifact_path(&prop, true)?)?;
+//! }
+//! # Ok::<(), ksmm::KsmmError>(())
+//! ```
+
+pub mod abi;
+pub mod build;
+pub mod changelog;
+pub mod check;
+pub mod config;
+pub mod error;
+pub mod i18n;
+pub mod ignore;
+pub mod module_prop;
+pub mod native;
+pub mod project;
+pub mod sign;
+pub mod update_json;
+pub mod variant;
+pub mod version;
+
+pub use abi::AbiBinaries;
+pub use build::{BuildPlan, FileOperation, OperationKind, Packager};
+pub use check::{Diagnostic, Severity};
+pub use config::{
+    Abi, AbiConfig, BuildConfig, Config, Forge, InitConfig, NativeConfig, Overrides, SignConfig, UpdateJsonConfig,
+    VariantConfig, VersionCodeStrategy,
+};
+pub use error::KsmmError;
+pub use ignore::{IgnoreRule, IgnoreRules};
+pub use module_prop::ModuleProp;
+pub use native::{NativeBinary, NativeBuild};
+pub use project::Project;
+pub use sign::Signer;
+pub use update_json::UpdateJson;
+pub use variant::Variant;
+pub use version::{Bump, Version};
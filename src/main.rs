@@ -1,7 +1,12 @@
-use clap::{Parser, Subcommand, builder::Styles, CommandFactory};
+use clap::{Parser, Subcommand, builder::Styles, CommandFactory, FromArgMatches};
 use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
 mod commands;
+mod template;
+
+use ksmm::i18n::{self, tr};
 
 #[derive(Parser)]
 #[command(
@@ -16,6 +21,10 @@ struct Cli {
     /// Show version information
     #[arg(short = 'V', long)]
     version: bool,
+
+    /// 界面语言 (默认根据 LANG 等环境变量检测)
+    #[arg(long, global = true, value_enum, value_name = "LANG")]
+    lang: Option<i18n::Lang>,
 }
 
 fn get_styles() -> Styles {
@@ -30,13 +39,24 @@ fn get_styles() -> Styles {
 #[derive(Subcommand)]
 enum Commands {
     /// 初始化模块
-    Init,
+    Init(Box<commands::init::InitArgs>),
     /// 构建模块
-    Build,
+    Build(commands::build::BuildArgs),
+    /// 提升模块版本号
+    Bump(commands::bump::BumpArgs),
+    /// 根据 git 历史生成更新日志
+    Changelog(commands::changelog::ChangelogArgs),
+    /// 检查模块中的常见问题
+    Check(commands::check::CheckArgs),
+    /// 将 Magisk 模块转换为 KernelSU 模块
+    Convert(commands::convert::ConvertArgs),
     /// 签名文件
     Sign {
         /// 要签名的文件
         file: String,
+        /// 使用的私钥 (覆盖配置中的 sign.key)
+        #[arg(long, value_name = "FILE")]
+        key: Option<PathBuf>,
     },
     /// 密钥管理
     Key {
@@ -47,29 +67,71 @@ enum Commands {
     Version,
 }
 
-fn main() {
+/// 按当前语言替换帮助文本，默认的中文帮助来自上面的文档注释
+fn localize(cmd: clap::Command) -> clap::Command {
+    cmd.about(tr!(help_ksmm))
+        .mut_arg("version", |a| a.help(tr!(help_version)))
+        .mut_arg("lang", |a| a.help(tr!(help_lang)))
+        .mut_subcommand("init", commands::init::localize)
+        .mut_subcommand("build", commands::build::localize)
+        .mut_subcommand("bump", commands::bump::localize)
+        .mut_subcommand("changelog", commands::changelog::localize)
+        .mut_subcommand("check", commands::check::localize)
+        .mut_subcommand("convert", commands::convert::localize)
+        .mut_subcommand("sign", |c| {
+            c.about(tr!(help_sign))
+                .mut_arg("file", |a| a.help(tr!(help_sign_file)))
+                .mut_arg("key", |a| a.help(tr!(help_sign_key)))
+        })
+        .mut_subcommand("key", commands::sign::localize)
+        .mut_subcommand("version", |c| c.about(tr!(help_version)))
+}
+
+fn main() -> ExitCode {
     unsafe {
         env::set_var("FORCE_COLOR", "1");
     }
 
-    let cli = Cli::parse();
+    // 帮助信息在解析参数时就会输出，所以先确定语言
+    let args: Vec<String> = env::args().collect();
+    i18n::init(i18n::lang_from_args(&args));
+
+    let matches = localize(Cli::command()).get_matches_from(&args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     // Handle version flag
     if cli.version {
         commands::version::execute();
-        return;
+        return ExitCode::SUCCESS;
     }
 
     // Handle commands
-    match cli.command {
-        Some(Commands::Build) => commands::build::execute(),
-        Some(Commands::Init) => commands::init::execute(),
-        Some(Commands::Sign { file }) => commands::sign::execute_sign_file(file),
+    let result = match cli.command {
+        Some(Commands::Build(args)) => commands::build::execute(args),
+        Some(Commands::Init(args)) => commands::init::execute(*args),
+        Some(Commands::Bump(args)) => commands::bump::execute(args),
+        Some(Commands::Changelog(args)) => commands::changelog::execute(args),
+        Some(Commands::Check(args)) => commands::check::execute(args),
+        Some(Commands::Convert(args)) => commands::convert::execute(args),
+        Some(Commands::Sign { file, key }) => commands::sign::execute_sign_file(file, key),
         Some(Commands::Key { key_command }) => commands::sign::execute_key_command(key_command),
-        Some(Commands::Version) => commands::version::execute(),
+        Some(Commands::Version) => {
+            commands::version::execute();
+            Ok(())
+        }
         None => {
             // No command provided, show help
-            let _ = Cli::command().print_help();
+            let _ = localize(Cli::command()).print_help();
+            Ok(())
+        }
+    };
+
+    // 错误统一输出到 stderr，退出码对应错误分类
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            e.exit_code()
         }
     }
 }
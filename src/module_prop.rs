@@ -0,0 +1,309 @@
+use chrono::{Datelike, Timelike, Utc};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::error::KsmmError;
+use crate::i18n::tr;
+
+/// module.prop 中的一行，`text` 保存原始内容（不含换行符）
+#[derive(Debug, Clone)]
+struct Line {
+    text: String,
+    entry: Option<(String, String)>,
+}
+
+impl Line {
+    fn parse(text: &str) -> Self {
+        let content = text.trim_end_matches('\r');
+        let entry = if content.trim_start().starts_with('#') {
+            None
+        } else {
+            content.split_once('=').map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        };
+        Line { text: text.to_string(), entry }
+    }
+}
+
+/// module.prop 中某个字段的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropError {
+    /// 出错的行号，从 1 开始；缺少字段时为 `None`
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for PropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "module.prop:{}: {}", line, self.message),
+            None => write!(f, "module.prop: {}", self.message),
+        }
+    }
+}
+
+impl From<PropError> for KsmmError {
+    fn from(e: PropError) -> Self {
+        KsmmError::ModuleProp(e)
+    }
+}
+
+/// module.prop 文件内容
+///
+/// 注释、空行、字段顺序和未知字段都会原样保留，只有通过 [`ModuleProp::set`] 修改的行会被重写。
+#[derive(Debug, Clone, Default)]
+pub struct ModuleProp {
+    lines: Vec<Line>,
+    trailing_newline: bool,
+}
+
+impl ModuleProp {
+    pub fn parse(content: &str) -> Self {
+        ModuleProp {
+            lines: content.split_terminator('\n').map(Line::parse).collect(),
+            trailing_newline: content.is_empty() || content.ends_with('\n'),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, KsmmError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| KsmmError::io(tr!(read_file_failed, path.display(), e), e))?;
+        Ok(Self::parse(&content))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), KsmmError> {
+        fs::write(path, self.to_string())
+            .map_err(|e| KsmmError::io(tr!(write_file_failed, path.display(), e), e))
+    }
+
+    /// 按顺序遍历所有字段，返回 (行号, key, value)
+    pub fn entries(&self) -> impl Iterator<Item = (usize, &str, &str)> {
+        self.lines.iter().enumerate().filter_map(|(index, line)| {
+            line.entry.as_ref().map(|(key, value)| (index + 1, key.as_str(), value.as_str()))
+        })
+    }
+
+    /// 取出第一个同名字段的值
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.find(key).map(|(_, value)| value)
+    }
+
+    /// 字段所在的行号
+    pub fn line_of(&self, key: &str) -> Option<usize> {
+        self.find(key).map(|(line, _)| line)
+    }
+
+    fn find(&self, key: &str) -> Option<(usize, &str)> {
+        self.entries().find(|(_, k, _)| *k == key).map(|(line, _, value)| (line, value))
+    }
+
+    /// 修改第一个同名字段，不存在时追加到末尾
+    pub fn set(&mut self, key: &str, value: impl Into<String>) {
+        let value = value.into();
+        let text = format!("{}={}", key, value);
+        match self.lines.iter_mut().find(|line| line.entry.as_ref().is_some_and(|(k, _)| k == key)) {
+            Some(line) => {
+                // 保留原有的 CRLF 换行
+                line.text = if line.text.ends_with('\r') { format!("{}\r", text) } else { text };
+                line.entry = Some((key.to_string(), value));
+            }
+            None => {
+                self.lines.push(Line { text, entry: Some((key.to_string(), value)) });
+                self.trailing_newline = true;
+            }
+        }
+    }
+
+    /// 取出必需字段，缺失或为空时报错
+    pub fn required(&self, key: &str) -> Result<&str, PropError> {
+        match self.find(key) {
+            Some((line, "")) => Err(PropError { line: Some(line), message: tr!(prop_empty_key, key) }),
+            Some((_, value)) => Ok(value),
+            None => Err(PropError { line: None, message: tr!(prop_missing_key, key) }),
+        }
+    }
+
+    pub fn id(&self) -> Result<&str, PropError> {
+        let id = self.required("id")?;
+        if !is_valid_id(id) {
+            return Err(PropError { line: self.line_of("id"), message: tr!(prop_invalid_id, id) });
+        }
+        Ok(id)
+    }
+
+    pub fn name(&self) -> Result<&str, PropError> {
+        self.required("name")
+    }
+
+    pub fn version(&self) -> Result<&str, PropError> {
+        self.required("version")
+    }
+
+    /// versionCode 在 Android 中是 `int`，超出 i32 范围的值同样报错
+    pub fn version_code(&self) -> Result<i64, PropError> {
+        let value = self.required("versionCode")?;
+        let code: i64 = value.parse().map_err(|_| PropError {
+            line: self.line_of("versionCode"),
+            message: tr!(prop_invalid_version_code, value),
+        })?;
+        if i32::try_from(code).is_err() {
+            return Err(PropError { line: self.line_of("versionCode"), message: tr!(prop_version_code_out_of_range, value) });
+        }
+        Ok(code)
+    }
+
+    pub fn set_version_code(&mut self, version_code: i64) {
+        self.set("versionCode", version_code.to_string());
+    }
+
+    pub fn author(&self) -> Result<&str, PropError> {
+        self.required("author")
+    }
+
+    pub fn description(&self) -> Result<&str, PropError> {
+        self.required("description")
+    }
+
+    /// updateJson 是可选字段，存在时必须是 http(s) 地址
+    pub fn update_json(&self) -> Result<Option<&str>, PropError> {
+        let Some((line, url)) = self.find("updateJson") else {
+            return Ok(None);
+        };
+        let url_regex = Regex::new(r"^https?://[^\s/]+(/\S*)?$").unwrap();
+        if !url_regex.is_match(url) {
+            return Err(PropError { line: Some(line), message: tr!(prop_invalid_update_json, url) });
+        }
+        Ok(Some(url))
+    }
+
+    /// 检查整个文件，返回所有问题
+    pub fn validate(&self) -> Vec<PropError> {
+        let mut errors = Vec::new();
+
+        let mut seen = HashMap::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            let content = line.text.trim();
+            match &line.entry {
+                None if !content.is_empty() && !content.starts_with('#') => errors.push(PropError {
+                    line: Some(index + 1),
+                    message: tr!(prop_malformed_line, content),
+                }),
+                Some((key, _)) => {
+                    if let Some(first) = seen.get(key.as_str()) {
+                        errors.push(PropError {
+                            line: Some(index + 1),
+                            message: tr!(prop_duplicate_key, key, first),
+                        });
+                    } else {
+                        seen.insert(key.as_str(), index + 1);
+                    }
+                }
+                None => {}
+            }
+        }
+
+        let checks = [
+            self.id().err(),
+            self.name().err(),
+            self.version().err(),
+            self.version_code().err(),
+            self.author().err(),
+            self.description().err(),
+            self.update_json().err(),
+        ];
+        errors.extend(checks.into_iter().flatten());
+        errors
+    }
+}
+
+impl fmt::Display for ModuleProp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, line) in self.lines.iter().enumerate() {
+            f.write_str(&line.text)?;
+            if index + 1 < self.lines.len() || self.trailing_newline {
+                f.write_str("\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 模块 ID 必须以字母开头，只能包含字母、数字、点、下划线和连字符
+pub fn is_valid_id(id: &str) -> bool {
+    Regex::new(r"^[a-zA-Z][a-zA-Z0-9._-]+$").unwrap().is_match(id)
+}
+
+/// 以当前 UTC 时间生成 YYYYMMDDHH 格式的 versionCode
+pub fn timestamp_version_code() -> i32 {
+    let now = Utc::now();
+    now.year() * 1000000 + now.month() as i32 * 10000 + now.day() as i32 * 100 + now.hour() as i32
+}
+
+/// `minutes_version_code` 的起点，大于 `timestamp_version_code` 在 2100 年之前生成的所有值
+const MINUTES_VERSION_CODE_BASE: i64 = 2_100_000_000;
+
+/// 2026-01-01T00:00:00Z 的 Unix 时间戳
+const MINUTES_VERSION_CODE_EPOCH: i64 = 1_767_225_600;
+
+/// 以 2100000000 加上自 2026 年以来的分钟数生成 versionCode，每分钟都不同
+///
+/// 从 `timestamp` 切换到这个策略时 versionCode 不会变小，在 2116 年之前都在 i32 范围内。
+///
+/// 代价是数值无法直接读出构建时间，并且占用了 i32 的大部分范围：一旦使用过这个策略，之后换成 `semver`、
+/// `git-count` 等数值更小的策略都会被拒绝，只能用 `ksmm bump --version-code` 明确重设。
+pub fn minutes_version_code() -> i64 {
+    MINUTES_VERSION_CODE_BASE + (Utc::now().timestamp() - MINUTES_VERSION_CODE_EPOCH) / 60
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROP: &str = "# module\r\nid=demo\r\nname = Demo Module\r\nversion=v1.0.0\r\nversionCode=100\r\ncustom=keep\r\n";
+
+    #[test]
+    fn round_trip_preserves_formatting() {
+        assert_eq!(ModuleProp::parse(PROP).to_string(), PROP);
+        let without_newline = "id=demo\nversion=v1";
+        assert_eq!(ModuleProp::parse(without_newline).to_string(), without_newline);
+    }
+
+    #[test]
+    fn set_rewrites_only_the_changed_line() {
+        let mut prop = ModuleProp::parse(PROP);
+        prop.set_version_code(101);
+        prop.set("updateJson", "https://example.com/update.json");
+        assert_eq!(
+            prop.to_string(),
+            "# module\r\nid=demo\r\nname = Demo Module\r\nversion=v1.0.0\r\nversionCode=101\r\ncustom=keep\r\nupdateJson=https://example.com/update.json\n"
+        );
+        assert_eq!(prop.get("name"), Some("Demo Module"));
+        assert_eq!(prop.line_of("custom"), Some(6));
+    }
+
+    #[test]
+    fn validate_reports_lines() {
+        let prop = ModuleProp::parse("id=demo\nid=again\nnot a field\nversionCode=x\n");
+        let errors = prop.validate();
+        assert!(errors.iter().any(|e| e.line == Some(2)));
+        assert!(errors.iter().any(|e| e.line == Some(3)));
+        assert!(errors.iter().any(|e| e.line == Some(4)));
+        assert!(errors.iter().any(|e| e.line.is_none()));
+    }
+
+    #[test]
+    fn version_code_must_fit_i32() {
+        assert_eq!(ModuleProp::parse("versionCode=2147483647\n").version_code(), Ok(2147483647));
+        assert!(ModuleProp::parse("versionCode=2147483648\n").version_code().is_err());
+        assert!(ModuleProp::parse("versionCode=abc\n").version_code().is_err());
+    }
+
+    #[test]
+    fn ids_must_start_with_a_letter() {
+        assert!(is_valid_id("my_module-2.x"));
+        assert!(!is_valid_id("2module"));
+        assert!(!is_valid_id("a"));
+        assert!(!is_valid_id("my module"));
+    }
+}
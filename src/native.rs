@@ -0,0 +1,192 @@
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::env;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::config::{self, Abi, NativeConfig, TemplateError};
+use crate::error::KsmmError;
+use crate::i18n::tr;
+use crate::project::Project;
+
+/// `native.dest` 中可用的占位符
+const DEST_PLACEHOLDERS: &[&str] = &["abi", "target"];
+
+/// 一个由 cargo 构建、将被打包的可执行文件
+#[derive(Debug, Clone)]
+pub struct NativeBinary {
+    /// 所属的 cargo 包
+    pub package: String,
+    /// Rust 目标三元组
+    pub target: String,
+    /// cargo 生成的文件
+    pub src: PathBuf,
+    /// 在模块中的路径，相对于模块根目录
+    pub dest: PathBuf,
+}
+
+/// `cargo metadata` 输出中用到的部分
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<MetadataPackage>,
+    target_directory: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct MetadataPackage {
+    name: String,
+    targets: Vec<MetadataTarget>,
+}
+
+#[derive(Deserialize)]
+struct MetadataTarget {
+    name: String,
+    kind: Vec<String>,
+}
+
+/// 按 `[native]` 用 cargo 交叉编译可执行文件
+///
+/// 创建时读取 `cargo metadata`，在构建之前就能确定每个可执行文件的输出路径与在模块中的位置，
+/// 复制计划与 `[abi]` 的检查因此可以在 cargo 运行之前完成。
+#[derive(Debug, Clone)]
+pub struct NativeBuild {
+    manifest: PathBuf,
+    config: NativeConfig,
+    binaries: Vec<NativeBinary>,
+}
+
+impl NativeBuild {
+    /// `[native]` 中没有配置包时返回 `None`
+    pub fn new(project: &Project) -> Result<Option<Self>, KsmmError> {
+        let config = project.config().native.clone();
+        if config.packages.is_empty() {
+            return Ok(None);
+        }
+        let manifest = project.root().join(config::expand_home(&config.manifest));
+        let metadata = metadata(&manifest)?;
+
+        let mut binaries = Vec::new();
+        let mut dests = BTreeSet::new();
+        for name in &config.packages {
+            let package = metadata
+                .packages
+                .iter()
+                .find(|package| &package.name == name)
+                .ok_or_else(|| KsmmError::Config(tr!(native_unknown_package, name, manifest.display())))?;
+            let bins: Vec<&str> = package
+                .targets
+                .iter()
+                .filter(|target| target.kind.iter().any(|kind| kind == "bin"))
+                .map(|target| target.name.as_str())
+                .collect();
+            if bins.is_empty() {
+                return Err(KsmmError::Config(tr!(native_no_binaries, name)));
+            }
+
+            for target in &config.targets {
+                let dir = dest_dir(&config.dest, target)?;
+                for bin in &bins {
+                    let dest = dir.join(bin);
+                    // 没有 {abi} 或 {target} 时不同目标的文件会落在同一个位置
+                    if !dests.insert(dest.clone()) {
+                        return Err(KsmmError::Config(tr!(native_dest_collision, dest.display(), config.dest)));
+                    }
+                    binaries.push(NativeBinary {
+                        package: name.clone(),
+                        target: target.clone(),
+                        src: metadata.target_directory.join(target).join("release").join(bin),
+                        dest,
+                    });
+                }
+            }
+        }
+        Ok(Some(NativeBuild { manifest, config, binaries }))
+    }
+
+    /// 所有目标的可执行文件，按包、目标排列
+    pub fn binaries(&self) -> &[NativeBinary] {
+        &self.binaries
+    }
+
+    pub fn targets(&self) -> &[String] {
+        &self.config.targets
+    }
+
+    /// 构建一个目标的 `cargo build` 命令，`strip` 时通过环境变量让 release 配置去掉符号
+    pub fn command(&self, target: &str) -> Command {
+        let mut command = Command::new(cargo());
+        command.args(["build", "--release", "--target", target]).arg("--manifest-path").arg(&self.manifest);
+        for package in &self.config.packages {
+            command.args(["--package", package]);
+        }
+        if self.config.strip {
+            command.env("CARGO_PROFILE_RELEASE_STRIP", "symbols");
+        }
+        command
+    }
+
+    /// 依次构建每个目标，`on_target` 在每个目标开始前调用；任何一个目标失败时立即停止
+    ///
+    /// cargo 的进度与错误直接输出到标准错误。
+    pub fn build(&self, mut on_target: impl FnMut(&str)) -> Result<(), KsmmError> {
+        for target in &self.config.targets {
+            on_target(target);
+            let status = self
+                .command(target)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .status()
+                .map_err(|e| KsmmError::Build(tr!(native_exec_failed, e)))?;
+            if !status.success() {
+                return Err(KsmmError::Build(tr!(native_build_failed, target, status)));
+            }
+            if let Some(binary) = self.binaries.iter().find(|binary| &binary.target == target && !binary.src.is_file()) {
+                return Err(KsmmError::Build(tr!(native_binary_missing, binary.src.display())));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 调用 ksmm 的 cargo 通过 `CARGO` 传入，否则使用 PATH 中的 cargo
+fn cargo() -> OsString {
+    env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo"))
+}
+
+/// 读取工作区中的包与目标目录
+fn metadata(manifest: &Path) -> Result<Metadata, KsmmError> {
+    let output = Command::new(cargo())
+        .args(["metadata", "--format-version", "1", "--no-deps", "--manifest-path"])
+        .arg(manifest)
+        .output()
+        .map_err(|e| KsmmError::Build(tr!(native_exec_failed, e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(KsmmError::Build(tr!(native_metadata_failed, manifest.display(), stderr.trim())));
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| KsmmError::Build(tr!(native_metadata_failed, manifest.display(), e)))
+}
+
+/// 展开 `native.dest`，结果必须是模块中的相对路径
+fn dest_dir(template: &str, target: &str) -> Result<PathBuf, KsmmError> {
+    let abi = Abi::from_target(target).map(Abi::name);
+    let lookup = |name: &str| match name {
+        "abi" => abi,
+        "target" => Some(target),
+        _ => None,
+    };
+    let dir = config::expand_template(template, lookup).map_err(|e| {
+        let reason = match e {
+            TemplateError::Unclosed => tr!(build_artifact_name_unclosed).to_string(),
+            TemplateError::Unknown(name) if name == "abi" => tr!(native_unknown_abi, target),
+            TemplateError::Unknown(name) => tr!(build_artifact_name_unknown, name, DEST_PLACEHOLDERS.join(", ")),
+        };
+        KsmmError::Config(tr!(native_bad_dest, template, reason))
+    })?;
+    let dir = PathBuf::from(dir);
+    if !dir.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir)) {
+        return Err(KsmmError::Config(tr!(native_bad_dest, template, tr!(native_dest_outside))));
+    }
+    Ok(dir)
+}
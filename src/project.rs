@@ -0,0 +1,693 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::abi::AbiBinaries;
+use crate::build::{self, BuildPlan, OUTPUT_MARKER};
+use crate::changelog::{self, ChangeEntry, ReleaseChanges, CHANGELOG_FILE};
+use crate::check::{self, Diagnostic};
+use crate::config::{self, Config, Overrides, VersionCodeStrategy};
+use crate::error::KsmmError;
+use crate::i18n::tr;
+use crate::ignore::IgnoreRules;
+use crate::module_prop::{self, ModuleProp, PropError};
+use crate::native::NativeBuild;
+use crate::sign::Signer;
+use crate::update_json::UpdateJson;
+use crate::variant::Variant;
+use crate::version::Version;
+
+/// `.ksmm/` 中 ksmm 自己使用的文件与目录，不能用作构建目录或发布目录
+const KSMM_RESERVED: &[&str] = &["key", "build.conf", "ksmm.toml", "convert-backup", "release-notes.md"];
+
+/// 旧版本使用的没有标记的默认输出目录
+const LEGACY_OUTPUT_DIRS: &[&str] = &[".ksmm/build", ".ksmm/release"];
+
+/// 一个 ksmm 模块项目，所有路径都相对于项目根目录
+#[derive(Debug, Clone)]
+pub struct Project {
+    root: PathBuf,
+    config: Config,
+}
+
+impl Project {
+    /// 打开模块项目，根目录中必须存在 module.prop
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, KsmmError> {
+        Self::open_with_overrides(root, &Overrides::default())
+    }
+
+    /// 打开模块项目，`overrides`（通常来自命令行参数）优先于环境变量与所有配置文件
+    pub fn open_with_overrides(root: impl Into<PathBuf>, overrides: &Overrides) -> Result<Self, KsmmError> {
+        let root = root.into();
+        if !root.join("module.prop").exists() {
+            return Err(KsmmError::ModulePropMissing);
+        }
+        let project = Project { config: Config::load(Some(&root), overrides)?, root };
+        project.check_output_dirs()?;
+        Ok(project)
+    }
+
+    /// 构建前会清空构建目录与发布目录，它们必须位于项目中，且不能包含会被打包的文件或 ksmm 自己的配置
+    ///
+    /// 允许的位置是 `.ksmm/` 中的新目录，或被根目录的 .gitignore / .ksmm/build.conf 忽略的目录。
+    fn check_output_dirs(&self) -> Result<(), KsmmError> {
+        let root = config::normalize_path(&self.root);
+        let ksmm_dir = config::normalize_path(&self.ksmm_dir());
+        let gitignore = IgnoreRules::load(&self.root, Path::new(".gitignore"), "")?;
+        let build_conf = IgnoreRules::load(&self.root, Path::new(build::BUILD_CONF), "")?;
+
+        let dirs = [
+            ("build_dir", config::normalize_path(&self.build_dir())),
+            ("release_dir", config::normalize_path(&self.release_dir())),
+        ];
+        for (name, dir) in &dirs {
+            if root.starts_with(dir) {
+                return Err(KsmmError::Config(tr!(config_bad_output_dir, name, dir.display())));
+            }
+            let Ok(rel) = dir.strip_prefix(&root) else {
+                return Err(KsmmError::Config(tr!(config_output_dir_outside, name, dir.display())));
+            };
+            if let Ok(inner) = dir.strip_prefix(&ksmm_dir) {
+                let first = inner.components().next().map(|c| c.as_os_str().to_string_lossy().into_owned());
+                match first {
+                    Some(first) if !KSMM_RESERVED.contains(&first.as_str()) => continue,
+                    _ => return Err(KsmmError::Config(tr!(config_output_dir_reserved, name, dir.display()))),
+                }
+            }
+
+            // 与 git 相同，上级目录被忽略时其中的内容都不会被打包
+            let mut prefix = String::new();
+            let ignored = rel.components().any(|component| {
+                if !prefix.is_empty() {
+                    prefix.push('/');
+                }
+                prefix.push_str(&component.as_os_str().to_string_lossy());
+                let rule = build_conf.decide(&prefix, true).or_else(|| gitignore.decide(&prefix, true));
+                rule.is_some_and(|rule| !rule.negated)
+            });
+            if !ignored {
+                return Err(KsmmError::Config(tr!(config_output_dir_packaged, name, dir.display())));
+            }
+        }
+
+        let [(_, build_dir), (_, release_dir)] = &dirs;
+        if build_dir.starts_with(release_dir) || release_dir.starts_with(build_dir) {
+            return Err(KsmmError::Config(tr!(config_same_output_dir, build_dir.display(), release_dir.display())));
+        }
+        Ok(())
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn module_prop_path(&self) -> PathBuf {
+        self.root.join("module.prop")
+    }
+
+    pub fn ksmm_dir(&self) -> PathBuf {
+        self.root.join(".ksmm")
+    }
+
+    /// 构建目录，默认为 .ksmm/build
+    pub fn build_dir(&self) -> PathBuf {
+        self.root.join(config::expand_home(&self.config.build.build_dir))
+    }
+
+    /// 发布目录，默认为 .ksmm/release
+    pub fn release_dir(&self) -> PathBuf {
+        self.root.join(config::expand_home(&self.config.build.release_dir))
+    }
+
+    pub fn key_dir(&self) -> PathBuf {
+        self.ksmm_dir().join("key")
+    }
+
+    pub fn module_prop(&self) -> Result<ModuleProp, KsmmError> {
+        ModuleProp::load(&self.module_prop_path())
+    }
+
+    /// 打开项目时合并的配置
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// 签名使用的私钥，见 [`Signer::configured_key`]
+    pub fn signing_key(&self) -> Result<Option<PathBuf>, KsmmError> {
+        Signer::configured_key(&self.root, &self.config.sign)
+    }
+
+    pub fn changelog_path(&self) -> PathBuf {
+        self.root.join(CHANGELOG_FILE)
+    }
+
+    /// module.prop 中的 version，必须是语义化版本
+    pub fn current_version(&self) -> Result<Version, KsmmError> {
+        let prop = self.module_prop()?;
+        let version = prop.version()?;
+        Version::parse(version).ok_or_else(|| {
+            KsmmError::from(PropError { line: prop.line_of("version"), message: tr!(bump_not_semver, version) })
+        })
+    }
+
+    /// 将 module.prop 中的 version 改为 `next`，新版本必须高于当前版本
+    pub fn set_version(&self, next: &Version) -> Result<ModuleProp, KsmmError> {
+        let current = self.current_version()?;
+        if *next <= current {
+            return Err(KsmmError::Input(tr!(bump_not_newer, next, current)));
+        }
+        let mut prop = self.module_prop()?;
+        prop.set("version", next.to_string());
+        prop.save(&self.module_prop_path())?;
+        Ok(prop)
+    }
+
+    /// 在 CHANGELOG.md 最前面添加该版本的段落，或填充已有的空段落，返回文件是否被修改
+    ///
+    /// 已有内容的段落视为手写内容，保持不变。
+    pub fn write_changelog_section(&self, version: &Version, body: &str) -> Result<bool, KsmmError> {
+        let path = self.changelog_path();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(KsmmError::io(tr!(read_file_failed, path.display(), e), e)),
+        };
+        let Some(content) = changelog::write_section(&content, &version.tag(), body) else {
+            return Ok(false);
+        };
+        fs::write(&path, content).map_err(|e| KsmmError::io(tr!(write_file_failed, path.display(), e), e))?;
+        Ok(true)
+    }
+
+    /// 上一个版本以来的提交
+    ///
+    /// HEAD 正好是当前版本的标签时（例如发布工作流中）取该标签与上一个 `v*` 标签之间的提交，否则取 HEAD 之前
+    /// 最近的 `v*` 标签之后的提交，标签之后的新提交因此不会被漏掉；不在仓库中或还没有提交时返回空列表。
+    pub fn release_changes(&self, version: &Version) -> Result<ReleaseChanges, KsmmError> {
+        if self.git_commit_hash()? == "unknown" {
+            return Ok(ReleaseChanges { since: None, until: "HEAD".to_string(), entries: Vec::new() });
+        }
+
+        let tag = version.tag();
+        let at_tag = self.git_tag_exists(&tag)?
+            && self.git(&["rev-parse", "HEAD"])?.trim() == self.git(&["rev-parse", &format!("{}^{{commit}}", tag)])?.trim();
+        let until = if at_tag { tag } else { "HEAD".to_string() };
+        let since = if until == "HEAD" {
+            self.git_describe("HEAD")?
+        } else {
+            self.git_describe(&format!("{}^", until))?
+        };
+
+        let range = match &since {
+            Some(since) => format!("{}..{}", since, until),
+            None => until.clone(),
+        };
+        // 字段之间用 0x1f 分隔，提交之间用 0x1e 分隔
+        let log = self.git(&["log", "--no-merges", "--format=%h%x1f%s%x1f%b%x1e", &range])?;
+        let entries = log
+            .split('\x1e')
+            .filter_map(|record| {
+                let mut fields = record.trim_start_matches('\n').splitn(3, '\x1f');
+                let commit = fields.next()?;
+                let subject = fields.next()?;
+                ChangeEntry::parse(commit, subject, fields.next().unwrap_or_default())
+            })
+            .collect();
+        Ok(ReleaseChanges { since, until, entries })
+    }
+
+    /// `rev` 之前最近的 `v*` 标签，`rev` 不存在（例如根提交的父提交）或之前没有标签时返回 `None`
+    fn git_describe(&self, rev: &str) -> Result<Option<String>, KsmmError> {
+        let exists = Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", rev)])
+            .current_dir(&self.root)
+            .output()
+            .map_err(|e| KsmmError::Git(tr!(git_command_failed, "rev-parse", e)))?;
+        if !exists.status.success() {
+            return Ok(None);
+        }
+
+        let output = Command::new("git")
+            .args(["describe", "--tags", "--abbrev=0", "--match", "v*", rev])
+            .current_dir(&self.root)
+            .env("LC_ALL", "C")
+            .output()
+            .map_err(|e| KsmmError::Git(tr!(git_command_failed, "describe", e)))?;
+        if output.status.success() {
+            return Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string()));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("No names found") || stderr.contains("cannot describe") || stderr.contains("No tags can describe") {
+            return Ok(None);
+        }
+        Err(KsmmError::Git(tr!(git_command_failed, "describe", stderr.trim())))
+    }
+
+    /// 当前分支，不在仓库中或处于分离 HEAD 时返回 `None`
+    pub fn git_branch(&self) -> Result<Option<String>, KsmmError> {
+        if self.git_commit_hash()? == "unknown" {
+            return Ok(None);
+        }
+        let branch = self.git(&["branch", "--show-current"])?;
+        Ok((!branch.is_empty()).then_some(branch))
+    }
+
+    /// 标签是否已经存在，不在仓库中时报错
+    pub fn git_tag_exists(&self, tag: &str) -> Result<bool, KsmmError> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet", &format!("refs/tags/{}", tag)])
+            .current_dir(&self.root)
+            .output()
+            .map_err(|e| KsmmError::Git(tr!(git_command_failed, "rev-parse", e)))?;
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => Err(KsmmError::Git(tr!(git_command_failed, "rev-parse", String::from_utf8_lossy(&output.stderr).trim()))),
+        }
+    }
+
+    /// 提交 module.prop 与 CHANGELOG.md 并创建附注标签，返回标签名
+    ///
+    /// 只提交这两个文件，暂存区中的其他改动保持原样。
+    pub fn tag_release(&self, version: &Version) -> Result<String, KsmmError> {
+        let tag = version.tag();
+        let mut files = vec!["module.prop"];
+        if self.changelog_path().exists() {
+            files.push(CHANGELOG_FILE);
+        }
+        let message = format!("chore(release): {}", tag);
+
+        self.git(&[&["add", "--"][..], &files].concat())?;
+        self.git(&[&["commit", "--quiet", "-m", &message, "--"][..], &files].concat())?;
+        self.git(&["tag", "-a", &tag, "-m", &tag])?;
+        Ok(tag)
+    }
+
+    /// 在项目根目录中执行 git，失败时带上 stderr
+    fn git(&self, args: &[&str]) -> Result<String, KsmmError> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.root)
+            .output()
+            .map_err(|e| KsmmError::Git(tr!(git_command_failed, args[0], e)))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let detail = if stderr.trim().is_empty() { stdout } else { stderr };
+            return Err(KsmmError::Git(tr!(git_command_failed, args[0], detail.trim())));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// 删除构建目录，返回目录原本是否存在
+    pub fn clean_build_dir(&self) -> Result<bool, KsmmError> {
+        self.clean_output_dir(&self.build_dir())
+    }
+
+    /// 删除发布目录，返回目录原本是否存在
+    pub fn clean_release_dir(&self) -> Result<bool, KsmmError> {
+        self.clean_output_dir(&self.release_dir())
+    }
+
+    /// 只删除由 ksmm 创建（带有 [`OUTPUT_MARKER`]）或为空的目录
+    ///
+    /// 旧版本创建的默认目录 .ksmm/build 与 .ksmm/release 没有标记，仍然可以删除。
+    fn clean_output_dir(&self, dir: &Path) -> Result<bool, KsmmError> {
+        let Ok(metadata) = fs::symlink_metadata(dir) else {
+            return Ok(false);
+        };
+        let dir_path = config::normalize_path(dir);
+        let legacy = LEGACY_OUTPUT_DIRS.iter().any(|legacy| config::normalize_path(&self.root.join(legacy)) == dir_path);
+        let empty = metadata.is_dir() && fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none());
+        if !legacy && !empty && !dir.join(OUTPUT_MARKER).is_file() {
+            return Err(KsmmError::Config(tr!(build_clean_unmarked, dir.display(), OUTPUT_MARKER)));
+        }
+        fs::remove_dir_all(dir).map_err(|e| KsmmError::io(tr!(build_clean_failed, e), e))?;
+        Ok(true)
+    }
+
+    /// 把 versionCode 直接设为 `code`，不做“不能变小”的检查，返回原来的值
+    ///
+    /// 切换 version_code 策略时用于跳出原策略生成的大数值，例如从 init 写入的 YYYYMMDDHH 换成 `semver`。
+    pub fn reset_version_code(&self, code: i64) -> Result<i64, KsmmError> {
+        let mut prop = self.module_prop()?;
+        let previous = prop.version_code()?;
+        prop.set_version_code(code);
+        prop.save(&self.module_prop_path())?;
+        Ok(previous)
+    }
+
+    /// 按配置的策略刷新 versionCode 并写回 module.prop
+    pub fn refresh_version_code(&self) -> Result<ModuleProp, KsmmError> {
+        let mut prop = self.module_prop()?;
+        prop.set_version_code(self.next_version_code(&prop, &self.config.version_code)?);
+        prop.save(&self.module_prop_path())?;
+        Ok(prop)
+    }
+
+    /// 按策略计算本次构建的 versionCode，不修改任何文件
+    ///
+    /// 结果必须在 i32 范围内，并且不能小于 module.prop 中现有的值，否则设备不会把新版本识别为更新。
+    pub fn next_version_code(&self, prop: &ModuleProp, strategy: &VersionCodeStrategy) -> Result<i64, KsmmError> {
+        let code = match strategy {
+            VersionCodeStrategy::Timestamp => module_prop::timestamp_version_code().into(),
+            VersionCodeStrategy::TimestampMinutes => module_prop::minutes_version_code(),
+            VersionCodeStrategy::Keep => prop.version_code()?,
+            VersionCodeStrategy::Increment => prop.version_code()? + 1,
+            VersionCodeStrategy::GitCount => self.git_commit_count()?,
+            VersionCodeStrategy::Semver => {
+                let version = prop.version()?;
+                Version::parse(version).and_then(|v| v.version_code()).ok_or_else(|| PropError {
+                    line: prop.line_of("version"),
+                    message: tr!(version_code_bad_semver, version),
+                })?
+            }
+            VersionCodeStrategy::Env { env: name } => {
+                let value = env::var(name).map_err(|_| KsmmError::Input(tr!(version_code_env_missing, name)))?;
+                value.trim().parse().map_err(|_| KsmmError::Input(tr!(version_code_env_invalid, name, value)))?
+            }
+        };
+        if i32::try_from(code).is_err() {
+            return Err(KsmmError::Input(tr!(version_code_out_of_range, code)));
+        }
+        // 现有的值缺失或无效时没有可比较的对象，由上面的策略或之后的检查报告
+        if let Ok(current) = prop.version_code()
+            && code < current
+        {
+            return Err(KsmmError::Config(tr!(version_code_lower, code, current)));
+        }
+        Ok(code)
+    }
+
+    /// 当前分支的提交数，不在仓库中或还没有提交时报错
+    fn git_commit_count(&self) -> Result<i64, KsmmError> {
+        if self.git_commit_hash()? == "unknown" {
+            return Err(KsmmError::Git(tr!(version_code_no_commits).to_string()));
+        }
+        let output = Command::new("git")
+            .args(["rev-list", "--count", "HEAD"])
+            .current_dir(&self.root)
+            .output()
+            .map_err(|e| KsmmError::Git(tr!(build_git_failed, e)))?;
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .map_err(|_| KsmmError::Git(tr!(build_git_failed, String::from_utf8_lossy(&output.stderr).trim())))
+    }
+
+    /// 获取 git 短提交哈希
+    ///
+    /// 没有安装 git、不在仓库中或还没有提交时使用 unknown；其余失败（例如 CI 中 safe.directory 检查不通过）会报错，
+    /// 避免发布带有错误版本号的 update.json。
+    pub fn git_commit_hash(&self) -> Result<String, KsmmError> {
+        let output = match Command::new("git")
+            .args(["rev-parse", "--is-inside-work-tree"])
+            .current_dir(&self.root)
+            .env("LC_ALL", "C")
+            .output()
+        {
+            Ok(output) => output,
+            Err(_) => return Ok("unknown".to_string()),
+        };
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("not a git repository") {
+                return Ok("unknown".to_string());
+            }
+            return Err(KsmmError::Git(tr!(build_git_failed, stderr.trim())));
+        }
+
+        let output = Command::new("git")
+            .args(["rev-parse", "--short", "--verify", "--quiet", "HEAD"])
+            .current_dir(&self.root)
+            .output()
+            .map_err(|e| KsmmError::Git(tr!(build_git_failed, e)))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Ok("unknown".to_string())
+        }
+    }
+
+    /// 打包使用的时间戳：优先使用 SOURCE_DATE_EPOCH，其次是最新提交的时间，都没有时返回 `None`
+    pub fn source_date_epoch(&self) -> Result<Option<i64>, KsmmError> {
+        if let Ok(value) = env::var("SOURCE_DATE_EPOCH") {
+            return match value.trim().parse() {
+                Ok(timestamp) => Ok(Some(timestamp)),
+                Err(_) => Err(KsmmError::Input(tr!(build_bad_source_date_epoch, value))),
+            };
+        }
+
+        // 与提交哈希相同的规则判断是否有提交，CI 中 git 本身出错时同样报错
+        if self.git_commit_hash()? == "unknown" {
+            return Ok(None);
+        }
+        let output = Command::new("git")
+            .args(["log", "-1", "--format=%ct"])
+            .current_dir(&self.root)
+            .output()
+            .map_err(|e| KsmmError::Git(tr!(build_git_failed, e)))?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+    }
+
+    /// 按 .ksmm/ksmm.toml 中的 `[update_json]` 准备 update.json，同时检查其中的模板
+    ///
+    /// `artifact` 是将要发布的 zip 的文件名，通常来自 [`crate::Packager::artifact_name`]。
+    pub fn update_json(&self, prop: &ModuleProp, short_commit: &str, artifact: &str) -> Result<UpdateJson, KsmmError> {
+        UpdateJson::new(prop, self.config.update_json.clone(), short_commit, self.git_branch()?.as_deref(), artifact)
+    }
+
+    /// 在发布目录中写入指向 `artifact` 的 update.json，变体写入 `update-<name>.json`
+    pub fn write_update_json(
+        &self,
+        update_json: &UpdateJson,
+        artifact: &Path,
+        variant: Option<&Variant>,
+    ) -> Result<PathBuf, KsmmError> {
+        let content = update_json.render(artifact)?;
+        let release_dir = self.release_dir();
+        build::create_output_dir(&release_dir)
+            .map_err(|e| KsmmError::io(tr!(build_create_release_dir_failed, e), e))?;
+
+        let path = match variant {
+            Some(variant) => release_dir.join(format!("update-{}.json", variant.name)),
+            None => release_dir.join("update.json"),
+        };
+        fs::write(&path, content).map_err(|e| KsmmError::io(tr!(build_update_json_failed, e), e))?;
+        Ok(path)
+    }
+
+    /// 根据 .gitignore 与 .ksmm/build.conf 计算需要复制到构建目录的文件
+    pub fn plan_build(&self) -> Result<BuildPlan, KsmmError> {
+        BuildPlan::new(self, None)
+    }
+
+    /// 在 [`Project::plan_build`] 的基础上加入变体的打包规则与覆盖目录
+    pub fn plan_build_for(&self, variant: Option<&Variant>) -> Result<BuildPlan, KsmmError> {
+        BuildPlan::new(self, variant)
+    }
+
+    /// `[native]` 中配置的 cargo 构建，没有配置包时返回 `None`
+    ///
+    /// 只读取 `cargo metadata`，不会开始构建；用 [`BuildPlan::add_native`] 把可执行文件加入复制计划。
+    pub fn native_build(&self) -> Result<Option<NativeBuild>, KsmmError> {
+        NativeBuild::new(self)
+    }
+
+    /// 复制计划中 `bin/<abi>/` 的可执行文件，并确认 `[abi]` 中声明的架构都没有缺少文件
+    pub fn abi_binaries(&self, plan: &BuildPlan) -> Result<AbiBinaries, KsmmError> {
+        let binaries = AbiBinaries::from_plan(plan, &self.build_dir())?;
+        binaries.check(&self.config.abi.abis)?;
+        Ok(binaries)
+    }
+
+    /// 配置中的所有变体，按名称排列
+    pub fn variants(&self) -> Vec<Variant> {
+        self.config.variants.iter().map(|(name, config)| Variant::new(name, config.clone())).collect()
+    }
+
+    /// 按名称查找变体
+    pub fn variant(&self, name: &str) -> Result<Variant, KsmmError> {
+        match self.config.variants.get(name) {
+            Some(config) => Ok(Variant::new(name, config.clone())),
+            None if self.config.variants.is_empty() => Err(KsmmError::Input(tr!(variant_none_configured).to_string())),
+            None => {
+                let names: Vec<&str> = self.config.variants.keys().map(String::as_str).collect();
+                Err(KsmmError::Input(tr!(variant_unknown, name, names.join(", "))))
+            }
+        }
+    }
+
+    /// 检查 module.prop、脚本和打包内容中的问题
+    pub fn check(&self) -> Result<Vec<Diagnostic>, KsmmError> {
+        check::check(self)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// 只有 module.prop 与 system/ 的最小项目
+    fn project_dir() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("module.prop"), "id=test\nversion=v1.0.0\nversionCode=1\n").unwrap();
+        fs::create_dir_all(dir.path().join("system/etc")).unwrap();
+        fs::write(dir.path().join("system/etc/example.conf"), "keep").unwrap();
+        dir
+    }
+
+    fn open_with_release_dir(dir: &Path, release_dir: &str) -> Result<Project, KsmmError> {
+        let mut overrides = Overrides::default();
+        overrides.set("build.release_dir", release_dir);
+        Project::open_with_overrides(dir, &overrides)
+    }
+
+    #[test]
+    fn output_dir_in_packaged_tree_is_rejected() {
+        let dir = project_dir();
+        assert!(matches!(open_with_release_dir(dir.path(), "system"), Err(KsmmError::Config(_))));
+        assert!(matches!(open_with_release_dir(dir.path(), "system/out"), Err(KsmmError::Config(_))));
+        assert!(dir.path().join("system/etc/example.conf").exists());
+    }
+
+    #[test]
+    fn output_dir_outside_project_is_rejected() {
+        let dir = project_dir();
+        let outside = tempfile::tempdir().unwrap();
+        let result = open_with_release_dir(dir.path(), &outside.path().to_string_lossy());
+        assert!(matches!(result, Err(KsmmError::Config(_))));
+        assert!(matches!(open_with_release_dir(dir.path(), ".."), Err(KsmmError::Config(_))));
+    }
+
+    #[test]
+    fn output_dir_overlapping_ksmm_files_is_rejected() {
+        let dir = project_dir();
+        for release_dir in [".ksmm", ".ksmm/key", ".ksmm/key/sub", ".ksmm/build"] {
+            assert!(matches!(open_with_release_dir(dir.path(), release_dir), Err(KsmmError::Config(_))), "{}", release_dir);
+        }
+        assert!(open_with_release_dir(dir.path(), ".ksmm/dist").is_ok());
+    }
+
+    #[test]
+    fn ignored_output_dir_is_accepted() {
+        let dir = project_dir();
+        assert!(open_with_release_dir(dir.path(), "dist").is_err());
+        fs::write(dir.path().join(".gitignore"), "dist/\n").unwrap();
+        assert!(open_with_release_dir(dir.path(), "dist").is_ok());
+        assert!(open_with_release_dir(dir.path(), "dist/release").is_ok());
+    }
+
+    #[test]
+    fn unmarked_output_dir_is_not_cleaned() {
+        let dir = project_dir();
+        let project = open_with_release_dir(dir.path(), ".ksmm/dist").unwrap();
+        let release_dir = project.release_dir();
+        fs::create_dir_all(&release_dir).unwrap();
+        fs::write(release_dir.join("precious"), "keep").unwrap();
+
+        assert!(matches!(project.clean_release_dir(), Err(KsmmError::Config(_))));
+        assert!(release_dir.join("precious").exists());
+    }
+
+    #[test]
+    fn marked_or_empty_output_dir_is_cleaned() {
+        let dir = project_dir();
+        let project = open_with_release_dir(dir.path(), ".ksmm/dist").unwrap();
+        let release_dir = project.release_dir();
+
+        fs::create_dir_all(&release_dir).unwrap();
+        assert!(project.clean_release_dir().unwrap());
+
+        build::create_output_dir(&release_dir).unwrap();
+        fs::write(release_dir.join("old.zip"), "zip").unwrap();
+        assert!(project.clean_release_dir().unwrap());
+        assert!(!release_dir.exists());
+        assert!(!project.clean_release_dir().unwrap());
+    }
+
+    #[test]
+    fn legacy_default_dirs_are_cleaned_without_marker() {
+        let dir = project_dir();
+        let project = Project::open(dir.path()).unwrap();
+        fs::create_dir_all(project.build_dir()).unwrap();
+        fs::write(project.build_dir().join("module.prop"), "id=test").unwrap();
+        assert!(project.clean_build_dir().unwrap());
+    }
+
+    #[test]
+    fn minutes_version_code_is_above_timestamp() {
+        let dir = project_dir();
+        let project = Project::open(dir.path()).unwrap();
+        let mut prop = ModuleProp::parse("id=test\nversion=v1.0.0\nversionCode=1\n");
+        prop.set_version_code(project.next_version_code(&prop, &VersionCodeStrategy::Timestamp).unwrap());
+        let minutes = project.next_version_code(&prop, &VersionCodeStrategy::TimestampMinutes).unwrap();
+        assert!(minutes > prop.version_code().unwrap());
+    }
+
+    #[test]
+    fn lower_version_code_is_rejected() {
+        let dir = project_dir();
+        let project = Project::open(dir.path()).unwrap();
+        let prop = ModuleProp::parse("id=test\nversion=v1.2.3\nversionCode=2026101712\n");
+        assert!(matches!(project.next_version_code(&prop, &VersionCodeStrategy::Semver), Err(KsmmError::Config(_))));
+        assert_eq!(project.next_version_code(&prop, &VersionCodeStrategy::Keep).unwrap(), 2026101712);
+    }
+
+    #[test]
+    fn reset_version_code_allows_lower_values() {
+        let dir = project_dir();
+        let project = Project::open(dir.path()).unwrap();
+        project.refresh_version_code().unwrap();
+        assert!(matches!(project.next_version_code(&project.module_prop().unwrap(), &VersionCodeStrategy::Semver), Err(KsmmError::Config(_))));
+        assert!(project.reset_version_code(10000).unwrap() > 10000);
+        let prop = project.module_prop().unwrap();
+        assert_eq!(project.next_version_code(&prop, &VersionCodeStrategy::Semver).unwrap(), 10000);
+    }
+
+    #[test]
+    fn version_code_must_fit_i32() {
+        let dir = project_dir();
+        let project = Project::open(dir.path()).unwrap();
+        let prop = ModuleProp::parse("id=test\nversion=v1.0.0\nversionCode=2147483647\n");
+        assert!(matches!(project.next_version_code(&prop, &VersionCodeStrategy::Increment), Err(KsmmError::Input(_))));
+        let prop = ModuleProp::parse("id=test\nversion=v1.0.0\nversionCode=2147483648\n");
+        assert!(matches!(project.next_version_code(&prop, &VersionCodeStrategy::Keep), Err(KsmmError::ModuleProp(_))));
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com", "-c", "commit.gpgsign=false", "-c", "tag.gpgsign=false"])
+            .args(args)
+            .current_dir(dir)
+            .stdout(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    #[test]
+    fn release_changes_include_commits_after_tag() {
+        let dir = project_dir();
+        git(dir.path(), &["init", "-q"]);
+        git(dir.path(), &["commit", "-q", "--allow-empty", "-m", "feat: first"]);
+        git(dir.path(), &["tag", "v1.0.0"]);
+        git(dir.path(), &["commit", "-q", "--allow-empty", "-m", "fix: after tag"]);
+
+        let project = Project::open(dir.path()).unwrap();
+        let version = Version::parse("1.0.0").unwrap();
+        let changes = project.release_changes(&version).unwrap();
+        assert_eq!(changes.until, "HEAD");
+        assert_eq!(changes.since.as_deref(), Some("v1.0.0"));
+        assert_eq!(changes.entries.len(), 1);
+
+        // HEAD 位于标签上时取标签之前的提交
+        git(dir.path(), &["checkout", "-q", "v1.0.0"]);
+        let changes = project.release_changes(&version).unwrap();
+        assert_eq!(changes.until, "v1.0.0");
+        assert_eq!(changes.entries.len(), 1);
+    }
+}
@@ -0,0 +1,137 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::config::{self, SignConfig};
+use crate::error::KsmmError;
+use crate::i18n::tr;
+
+// 嵌入 zakosign 二进制文件
+static ZAKOSIGN_BINARY: &[u8] = include_bytes!("bin/macos/arm64/zakosign");
+
+fn get_zakosign_path() -> io::Result<PathBuf> {
+    // 创建临时目录存放 zakosign 二进制文件
+    let temp_dir = env::temp_dir();
+    let zakosign_path = temp_dir.join("zakosign");
+
+    // 如果文件不存在，则写入嵌入的二进制文件
+    if !zakosign_path.exists() {
+        fs::write(&zakosign_path, ZAKOSIGN_BINARY)?;
+
+        // 设置执行权限
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&zakosign_path, fs::Permissions::from_mode(0o755))?;
+        }
+    }
+
+    Ok(zakosign_path)
+}
+
+fn zakosign() -> Result<PathBuf, KsmmError> {
+    get_zakosign_path().map_err(|e| KsmmError::io(tr!(sign_zakosign_failed, e), e))
+}
+
+/// 使用 zakosign 和 .pem 私钥签名文件
+#[derive(Debug, Clone)]
+pub struct Signer {
+    key: PathBuf,
+}
+
+impl Signer {
+    pub fn new(key: impl Into<PathBuf>) -> Self {
+        Signer { key: key.into() }
+    }
+
+    /// 按文件名顺序取密钥目录中的第一个 .pem 文件，目录不存在或没有密钥时返回 `None`
+    pub fn find_key(key_dir: &Path) -> Result<Option<PathBuf>, KsmmError> {
+        if !key_dir.exists() {
+            return Ok(None);
+        }
+
+        let mut keys = fs::read_dir(key_dir)
+            .map_err(|e| KsmmError::io(tr!(sign_read_key_dir_failed, e), e))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "pem"))
+            .collect::<Vec<_>>();
+        keys.sort();
+        Ok(keys.into_iter().next())
+    }
+
+    /// 配置中的 `sign.key`（相对路径相对于 `root`），未设置时在 `root` 的 .ksmm/key 中查找
+    ///
+    /// 配置了密钥但文件不存在时报错，避免静默地发布未签名的 zip。
+    pub fn configured_key(root: &Path, config: &SignConfig) -> Result<Option<PathBuf>, KsmmError> {
+        let Some(key) = &config.key else {
+            return Self::find_key(&root.join(".ksmm/key"));
+        };
+        let key = root.join(config::expand_home(key));
+        if !key.is_file() {
+            return Err(KsmmError::Sign(tr!(sign_configured_key_missing, key.display())));
+        }
+        Ok(Some(key))
+    }
+
+    pub fn key(&self) -> &Path {
+        &self.key
+    }
+
+    /// 签名文件并写入 `output_file`，两者相同时用签名后的版本替换原文件
+    pub fn sign_to(&self, file: &Path, output_file: &Path) -> Result<(), KsmmError> {
+        if output_file == file {
+            let mut temp = file.as_os_str().to_owned();
+            temp.push(".signing");
+            let temp = PathBuf::from(temp);
+            self.sign_to(file, &temp)?;
+            return fs::rename(&temp, file).map_err(|e| KsmmError::io(tr!(sign_replace_failed, file.display(), e), e));
+        }
+
+        let output = Command::new(zakosign()?)
+            .arg("sign")
+            .arg("--key")
+            .arg(&self.key)
+            .arg("--output")
+            .arg(output_file)
+            .arg("-f")
+            .arg(file)
+            .output()
+            .map_err(|e| KsmmError::Sign(tr!(sign_exec_failed, e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(KsmmError::Sign(format!("{}\n{}", tr!(sign_failed), tr!(sign_stderr, stderr.trim()))));
+        }
+        if !output_file.exists() {
+            return Err(KsmmError::Sign(tr!(build_signed_file_missing, output_file.display())));
+        }
+        Ok(())
+    }
+
+    /// 创建新的密钥对，zakosign 会直接写入 `path`
+    pub fn create_key(path: &Path) -> Result<(), KsmmError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| KsmmError::io(tr!(key_create_dir_failed, e), e))?;
+        }
+
+        let output = Command::new(zakosign()?)
+            .args(["key", "new"])
+            .arg(path)
+            .output()
+            .map_err(|e| KsmmError::Sign(tr!(key_exec_failed, e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            return Err(KsmmError::Sign(format!(
+                "{}\n{}\n{}",
+                tr!(key_failed),
+                tr!(sign_stderr, stderr.trim()),
+                tr!(key_stdout, stdout.trim())
+            )));
+        }
+        Ok(())
+    }
+}
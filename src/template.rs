@@ -0,0 +1,541 @@
+use owo_colors::OwoColorize;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use ksmm::i18n::{tr, Message};
+
+/// 模板中的单个文件，路径使用 `/` 分隔
+#[derive(Debug, Clone)]
+pub struct TemplateFile {
+    pub path: String,
+    pub content: Vec<u8>,
+    pub mode: Option<u32>,
+}
+
+/// 内置模板由若干文件层组成，后面的层覆盖前面的同名文件
+pub struct BuiltinTemplate {
+    pub name: &'static str,
+    pub description: Message,
+    layers: &'static [&'static [(&'static str, &'static str)]],
+}
+
+/// ksmm 项目本身需要的文件，导入已有模块时也会添加
+pub const PROJECT_LAYER: &[(&str, &str)] = &[
+    (".ksmm/build.conf", ksmm::build::DEFAULT_BUILD_CONF),
+    (".github/workflows/ci.yml", include_str!("templates/common/.github/workflows/ci.yml")),
+];
+
+const COMMON_LAYER: &[(&str, &str)] = &[
+    ("module.prop", include_str!("templates/common/module.prop")),
+    ("customize.sh", include_str!("templates/common/customize.sh")),
+    ("post-fs-data.sh", include_str!("templates/common/post-fs-data.sh")),
+    ("service.sh", include_str!("templates/common/service.sh")),
+    ("CHANGELOG.md", include_str!("templates/common/CHANGELOG.md")),
+    ("system/etc/example.conf", include_str!("templates/common/system/etc/example.conf")),
+];
+
+pub const ACTION_LAYER: &[(&str, &str)] = &[
+    ("action.sh", include_str!("templates/action/action.sh")),
+];
+
+pub const WEBUI_LAYER: &[(&str, &str)] = &[
+    ("webroot/index.html", include_str!("templates/webui/webroot/index.html")),
+];
+
+const BINARY_LAYER: &[(&str, &str)] = &[
+    ("customize.sh", include_str!("templates/binary/customize.sh")),
+    ("system/bin/{{id}}", include_str!("templates/binary/system/bin/{{id}}")),
+];
+
+pub const BUILTIN_TEMPLATES: &[BuiltinTemplate] = &[
+    BuiltinTemplate {
+        name: "minimal",
+        description: |c| c.template_minimal,
+        layers: &[COMMON_LAYER, PROJECT_LAYER],
+    },
+    BuiltinTemplate {
+        name: "webui",
+        description: |c| c.template_webui,
+        layers: &[COMMON_LAYER, PROJECT_LAYER, ACTION_LAYER, WEBUI_LAYER],
+    },
+    BuiltinTemplate {
+        name: "action-only",
+        description: |c| c.template_action_only,
+        layers: &[COMMON_LAYER, PROJECT_LAYER, ACTION_LAYER],
+    },
+    BuiltinTemplate {
+        name: "binary",
+        description: |c| c.template_binary,
+        layers: &[COMMON_LAYER, PROJECT_LAYER, BINARY_LAYER],
+    },
+];
+
+/// 本地模板根目录下的清单文件，不会被复制到新项目
+pub const MANIFEST_FILE: &str = "template.toml";
+
+/// 模板清单：自定义问题与按回答决定是否生成的文件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub description: Option<String>,
+    #[serde(default)]
+    pub prompts: Vec<TemplatePrompt>,
+    #[serde(default)]
+    pub files: Vec<FileRule>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptKind {
+    #[default]
+    Text,
+    Bool,
+    Choice,
+}
+
+/// 模板清单中声明的一个问题，回答保存为同名占位符
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplatePrompt {
+    pub key: String,
+    #[serde(rename = "type", default)]
+    pub kind: PromptKind,
+    pub message: String,
+    pub default: Option<toml::Value>,
+    #[serde(default)]
+    pub choices: Vec<String>,
+    /// 文本回答必须匹配的正则表达式
+    pub validate: Option<String>,
+    /// 只有条件成立时才提问
+    pub when: Option<String>,
+}
+
+impl TemplatePrompt {
+    /// 默认值，字符串默认值中的占位符会先被替换
+    pub fn default_value(&self, vars: &BTreeMap<String, String>) -> Option<String> {
+        match &self.default {
+            Some(toml::Value::String(s)) => Some(render_placeholders(s, vars)),
+            Some(toml::Value::Boolean(b)) => Some(b.to_string()),
+            Some(other) => Some(other.to_string()),
+            None if self.kind == PromptKind::Bool => Some("false".to_string()),
+            None if self.kind == PromptKind::Choice => self.choices.first().cloned(),
+            None => None,
+        }
+    }
+
+    /// 检查回答是否合法，返回规范化后的值
+    pub fn check_answer(&self, answer: &str) -> Result<String, String> {
+        match self.kind {
+            PromptKind::Bool => match answer.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "y" | "1" => Ok("true".to_string()),
+                "false" | "no" | "n" | "0" => Ok("false".to_string()),
+                _ => Err(tr!(template_invalid_bool, self.key, answer)),
+            },
+            PromptKind::Choice => {
+                if self.choices.iter().any(|c| c == answer) {
+                    Ok(answer.to_string())
+                } else {
+                    Err(tr!(template_invalid_choice, self.key, answer, self.choices.join(", ")))
+                }
+            }
+            PromptKind::Text => {
+                if let Some(pattern) = &self.validate {
+                    let regex = Regex::new(pattern).map_err(|e| format!("{}: {}", self.key, e))?;
+                    if !regex.is_match(answer) {
+                        return Err(tr!(template_invalid_text, self.key, answer, pattern));
+                    }
+                }
+                Ok(answer.to_string())
+            }
+        }
+    }
+}
+
+/// 文件规则：匹配 `paths` 的文件只在 `when` 成立时生成
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileRule {
+    pub paths: Vec<String>,
+    pub when: String,
+}
+
+impl Manifest {
+    fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let manifest: Manifest = toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        manifest.check().map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(manifest)
+    }
+
+    /// 加载时检查清单，避免提问到一半才发现错误
+    fn check(&self) -> Result<(), String> {
+        let key_regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+        let mut keys = BTreeSet::new();
+
+        for prompt in &self.prompts {
+            if !key_regex.is_match(&prompt.key) {
+                return Err(tr!(template_bad_key, prompt.key));
+            }
+            if !keys.insert(prompt.key.as_str()) {
+                return Err(tr!(template_duplicate_key, prompt.key));
+            }
+            if let Some(pattern) = &prompt.validate {
+                Regex::new(pattern).map_err(|e| tr!(template_bad_validate, prompt.key, e))?;
+            }
+            if prompt.kind == PromptKind::Choice && prompt.choices.is_empty() {
+                return Err(tr!(template_choice_without_choices, prompt.key));
+            }
+            if let Some(when) = &prompt.when {
+                Condition::parse(when)?;
+            }
+            if let Some(default) = prompt.default_value(&BTreeMap::new())
+                && prompt.kind != PromptKind::Text
+            {
+                prompt.check_answer(&default)?;
+            }
+        }
+
+        for rule in &self.files {
+            Condition::parse(&rule.when)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 条件表达式：`key`、`!key`、`key == "value"`、`key != "value"`，可用 `&&` 与 `||` 组合
+///
+/// 值可以使用单引号或双引号，引号中的 `&&`、`||` 等按普通字符处理。
+#[derive(Debug, Clone)]
+pub struct Condition {
+    // 外层为 ||，内层为 &&
+    any: Vec<Vec<ConditionAtom>>,
+}
+
+#[derive(Debug, Clone)]
+enum ConditionAtom {
+    Truthy(String),
+    Falsy(String),
+    Eq(String, String),
+    Ne(String, String),
+}
+
+impl Condition {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let key_regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+        let check_key = |key: &str| {
+            if key_regex.is_match(key) {
+                Ok(key.to_string())
+            } else {
+                Err(tr!(template_bad_condition, expr))
+            }
+        };
+
+        let mut any = Vec::new();
+        // 先找出引号中的内容，值里的 `||`、`&&`、`==` 不会被当作运算符
+        for branch in split_outside_quotes(expr, "||").ok_or_else(|| tr!(template_bad_condition, expr))? {
+            let mut all = Vec::new();
+            for atom in split_outside_quotes(branch, "&&").ok_or_else(|| tr!(template_bad_condition, expr))? {
+                let atom = atom.trim();
+                let ne = find_outside_quotes(atom, "!=");
+                let eq = find_outside_quotes(atom, "==");
+                let parsed = match (ne, eq) {
+                    (Some(i), eq) if eq.is_none_or(|eq| i < eq) => {
+                        ConditionAtom::Ne(check_key(atom[..i].trim())?, unquote(atom[i + 2..].trim()))
+                    }
+                    (_, Some(i)) => ConditionAtom::Eq(check_key(atom[..i].trim())?, unquote(atom[i + 2..].trim())),
+                    _ => match atom.strip_prefix('!') {
+                        Some(key) => ConditionAtom::Falsy(check_key(key.trim())?),
+                        None => ConditionAtom::Truthy(check_key(atom)?),
+                    },
+                };
+                all.push(parsed);
+            }
+            any.push(all);
+        }
+
+        Ok(Condition { any })
+    }
+
+    pub fn eval(&self, vars: &BTreeMap<String, String>) -> bool {
+        let truthy = |key: &str| vars.get(key).is_some_and(|v| !v.is_empty() && v != "false");
+        self.any.iter().any(|all| {
+            all.iter().all(|atom| match atom {
+                ConditionAtom::Truthy(key) => truthy(key),
+                ConditionAtom::Falsy(key) => !truthy(key),
+                ConditionAtom::Eq(key, value) => vars.get(key).is_some_and(|v| v == value),
+                ConditionAtom::Ne(key, value) => vars.get(key).is_none_or(|v| v != value),
+            })
+        })
+    }
+}
+
+/// 去掉值两侧成对的单引号或双引号
+fn unquote(value: &str) -> String {
+    ['"', '\'']
+        .into_iter()
+        .find_map(|quote| value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)))
+        .unwrap_or(value)
+        .to_string()
+}
+
+/// `pattern` 在引号之外第一次出现的位置，引号没有闭合时返回 `None`
+fn find_outside_quotes(text: &str, pattern: &str) -> Option<usize> {
+    scan_quotes(text, pattern).ok().flatten()
+}
+
+/// 按引号之外的 `separator` 分割，引号没有闭合时返回 `None`
+fn split_outside_quotes<'a>(text: &'a str, separator: &str) -> Option<Vec<&'a str>> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(index) = scan_quotes(rest, separator).ok()? {
+        parts.push(&rest[..index]);
+        rest = &rest[index + separator.len()..];
+    }
+    parts.push(rest);
+    Some(parts)
+}
+
+/// 跳过单引号或双引号中的内容查找 `pattern`，引号没有闭合时返回 `Err`
+fn scan_quotes(text: &str, pattern: &str) -> Result<Option<usize>, ()> {
+    let mut quote = None;
+    for (index, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if text[index..].starts_with(pattern) => return Ok(Some(index)),
+            None => {}
+        }
+    }
+    if quote.is_some() { Err(()) } else { Ok(None) }
+}
+
+/// 文件规则中的路径模式：`*` 不跨越 `/`，`**` 可以跨越；模式同时匹配该路径下的所有内容
+fn path_pattern_matches(pattern: &str, path: &str) -> bool {
+    let mut regex_pattern = String::from("^");
+    let mut chars = pattern.trim_start_matches('/').trim_end_matches('/').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex_pattern.push_str(".*");
+            }
+            '*' => regex_pattern.push_str("[^/]*"),
+            '?' => regex_pattern.push_str("[^/]"),
+            c => regex_pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex_pattern.push_str("(/.*)?$");
+    Regex::new(&regex_pattern).is_ok_and(|r| r.is_match(path))
+}
+
+/// 已加载到内存中的模板
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub name: String,
+    pub files: Vec<TemplateFile>,
+    pub dirs: Vec<String>,
+    pub manifest: Manifest,
+}
+
+impl Template {
+    /// 按名称查找内置模板，或者从本地目录加载模板
+    ///
+    /// 内置模板名称优先，本地目录与内置模板重名时可以写成 `./name`。
+    pub fn resolve(name_or_dir: &str) -> Result<Self, String> {
+        if let Some(builtin) = BUILTIN_TEMPLATES.iter().find(|t| t.name == name_or_dir) {
+            return Ok(Self::from_builtin(builtin));
+        }
+
+        let dir = Path::new(name_or_dir);
+        if dir.is_dir() {
+            return Self::from_dir(dir);
+        }
+
+        let names: Vec<&str> = BUILTIN_TEMPLATES.iter().map(|t| t.name).collect();
+        Err(tr!(template_not_found, name_or_dir, names.join(", ")))
+    }
+
+    pub fn from_builtin(builtin: &BuiltinTemplate) -> Self {
+        let mut template = Self::empty(builtin.name);
+        for layer in builtin.layers {
+            template.add_layer(layer);
+        }
+        template
+    }
+
+    pub fn empty(name: &str) -> Self {
+        Template {
+            name: name.to_string(),
+            files: Vec::new(),
+            dirs: Vec::new(),
+            manifest: Manifest::default(),
+        }
+    }
+
+    /// 叠加一层内置文件，同名文件会被覆盖
+    pub fn add_layer(&mut self, layer: &[(&str, &str)]) {
+        for (path, content) in layer {
+            let file = TemplateFile { path: path.to_string(), content: content.as_bytes().to_vec(), mode: None };
+            match self.files.iter_mut().find(|f| f.path == file.path) {
+                Some(existing) => *existing = file,
+                None => self.files.push(file),
+            }
+        }
+    }
+
+    pub fn from_dir(dir: &Path) -> Result<Self, String> {
+        let name = dir.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("local")
+            .to_string();
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let manifest = if manifest_path.is_file() {
+            Manifest::load(&manifest_path)?
+        } else {
+            Manifest::default()
+        };
+
+        let mut template = Template { name, files: Vec::new(), dirs: Vec::new(), manifest };
+        collect_template_files(dir, dir, &mut template)
+            .map_err(|e| tr!(template_read_dir_failed, dir.display(), e))?;
+        template.files.sort_by(|a, b| a.path.cmp(&b.path));
+        template.dirs.sort();
+        Ok(template)
+    }
+
+    /// 根据清单中的文件规则判断某个模板路径是否应当生成
+    fn is_emitted(&self, path: &str, vars: &BTreeMap<String, String>) -> bool {
+        self.manifest.files.iter()
+            .filter(|rule| rule.paths.iter().any(|p| path_pattern_matches(p, path)))
+            .all(|rule| Condition::parse(&rule.when).is_ok_and(|c| c.eval(vars)))
+    }
+
+    /// 将模板渲染到目标目录，已存在的文件不会被覆盖
+    pub fn render_into(&self, base_path: &Path, vars: &BTreeMap<String, String>) -> io::Result<()> {
+        for dir in self.dirs.iter().filter(|d| self.is_emitted(d, vars)) {
+            let dst = base_path.join(render_placeholders(dir, vars));
+            if !dst.exists() {
+                fs::create_dir_all(&dst)?;
+                println!("{} {}", "[+]".green(), tr!(init_created_dir, render_placeholders(dir, vars)));
+            }
+        }
+
+        for file in self.files.iter().filter(|f| self.is_emitted(&f.path, vars)) {
+            let rel_path = render_placeholders(&file.path, vars);
+            let dst = base_path.join(&rel_path);
+            if dst.exists() {
+                println!("{}", format!("  [!] {}", tr!(init_exists_skip, rel_path)).dimmed());
+                continue;
+            }
+
+            if let Some(parent) = dst.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            // 非 UTF-8 文件按原样复制
+            match std::str::from_utf8(&file.content) {
+                Ok(text) => fs::write(&dst, render_placeholders(text, vars))?,
+                Err(_) => fs::write(&dst, &file.content)?,
+            }
+
+            #[cfg(unix)]
+            if let Some(mode) = file.mode {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&dst, fs::Permissions::from_mode(mode))?;
+            }
+
+            println!("{} {}", "[+]".green(), tr!(init_created_file, rel_path));
+        }
+
+        Ok(())
+    }
+}
+
+fn collect_template_files(root: &Path, current: &Path, template: &mut Template) -> io::Result<()> {
+    for entry in fs::read_dir(current)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        // 模板自身的版本控制目录和清单不属于模板内容
+        if entry.file_name() == ".git" || (current == root && entry.file_name() == MANIFEST_FILE) {
+            continue;
+        }
+
+        let rel_path = relative_slash_path(root, &path);
+        if path.is_dir() {
+            template.dirs.push(rel_path);
+            collect_template_files(root, &path, template)?;
+        } else {
+            #[cfg(unix)]
+            let mode = {
+                use std::os::unix::fs::PermissionsExt;
+                Some(entry.metadata()?.permissions().mode() & 0o777)
+            };
+            #[cfg(not(unix))]
+            let mode = None;
+
+            template.files.push(TemplateFile { path: rel_path, content: fs::read(&path)?, mode });
+        }
+    }
+    Ok(())
+}
+
+fn relative_slash_path(root: &Path, path: &Path) -> String {
+    let rel: PathBuf = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 替换 `{{key}}` 占位符；未知的占位符（例如 GitHub Actions 的 `${{ env.X }}`）保持原样
+pub fn render_placeholders(text: &str, vars: &BTreeMap<String, String>) -> String {
+    let placeholder_regex = Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap();
+    placeholder_regex
+        .replace_all(text, |caps: &Captures| match vars.get(&caps[1]) {
+            Some(value) => value.clone(),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn condition_combines_and_or() {
+        let condition = Condition::parse("a && !b || c == \"x\"").unwrap();
+        assert!(condition.eval(&vars(&[("a", "true"), ("b", "false")])));
+        assert!(!condition.eval(&vars(&[("a", "true"), ("b", "true")])));
+        assert!(condition.eval(&vars(&[("c", "x")])));
+        assert!(!condition.eval(&vars(&[("c", "y")])));
+    }
+
+    #[test]
+    fn condition_keeps_operators_inside_quotes() {
+        let condition = Condition::parse("name == 'a||b' && mode != \"x&&y\"").unwrap();
+        assert!(condition.eval(&vars(&[("name", "a||b"), ("mode", "z")])));
+        assert!(!condition.eval(&vars(&[("name", "a"), ("mode", "z")])));
+        assert!(!condition.eval(&vars(&[("name", "a||b"), ("mode", "x&&y")])));
+
+        let condition = Condition::parse("name == 'a!=b'").unwrap();
+        assert!(condition.eval(&vars(&[("name", "a!=b")])));
+    }
+
+    #[test]
+    fn condition_rejects_bad_input() {
+        assert!(Condition::parse("name == 'a||b").is_err());
+        assert!(Condition::parse("1abc").is_err());
+        assert!(Condition::parse("a ||").is_err());
+    }
+}
@@ -0,0 +1,2 @@
+#!/system/bin/sh
+# 执行按钮脚本
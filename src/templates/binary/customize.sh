@@ -0,0 +1,10 @@
+#!/system/bin/sh
+# KernelSU 模块自定义安装脚本
+
+ui_print "- 设备架构: $ARCH"
+ui_print "- Android API: $API"
+
+# 设置可执行文件权限
+set_perm $MODPATH/system/bin/{{id}} 0 0 0755
+
+ui_print "- 模块安装完成"
@@ -0,0 +1,2 @@
+#!/system/bin/sh
+# 在文件系统挂载后执行
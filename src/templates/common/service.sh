@@ -0,0 +1,2 @@
+#!/system/bin/sh
+# 服务脚本
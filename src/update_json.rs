@@ -0,0 +1,295 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use crate::config::{self, Forge, TemplateError, UpdateJsonConfig};
+use crate::error::KsmmError;
+use crate::i18n::tr;
+use crate::module_prop::ModuleProp;
+use crate::version::Version;
+
+/// URL 模板中可用的占位符
+///
+/// `repository`、`host`、`owner`、`repo` 来自仓库地址，`base_url` 来自 updateJson 所在的目录。
+pub const PLACEHOLDERS: &[&str] = &[
+    "id",
+    "version",
+    "versionCode",
+    "commit",
+    "tag",
+    "branch",
+    "artifact",
+    "repository",
+    "host",
+    "owner",
+    "repo",
+    "base_url",
+];
+
+/// update.json 中固定的字段，`[update_json.extra]` 不能覆盖
+const RESERVED_FIELDS: &[&str] = &["changelog", "version", "versionCode", "zipUrl", "sha256"];
+
+/// module.prop 中没有 updateJson 且没有配置仓库时使用的地址，与旧版本的行为一致
+const FALLBACK_REPOSITORY: &str = "https://github.com/unknown/repo";
+
+impl Forge {
+    /// 预设的 zipUrl 与 changelog 模板
+    ///
+    /// GitHub 保持旧版本的 `releases/latest/download` 地址，不要求标签的命名；其他平台的下载地址指向
+    /// 与版本对应的标签，生成的发布工作流会为 `v*` 标签创建发布。
+    pub fn templates(self) -> (&'static str, &'static str) {
+        match self {
+            Forge::Github => (
+                "{repository}/releases/latest/download/{artifact}",
+                "https://raw.githubusercontent.com/{owner}/{repo}/{branch}/CHANGELOG.md",
+            ),
+            Forge::Gitlab => (
+                "{repository}/-/releases/{tag}/downloads/{artifact}",
+                "{repository}/-/raw/{branch}/CHANGELOG.md",
+            ),
+            Forge::Gitea | Forge::Forgejo => (
+                "{repository}/releases/download/{tag}/{artifact}",
+                "{repository}/raw/branch/{branch}/CHANGELOG.md",
+            ),
+            Forge::Static => ("{base_url}/{artifact}", "{base_url}/CHANGELOG.md"),
+        }
+    }
+}
+
+/// URL 模板中占位符的取值
+#[derive(Debug, Clone, Default)]
+pub struct UrlVars {
+    values: BTreeMap<&'static str, String>,
+}
+
+impl UrlVars {
+    /// 收集占位符的值，`branch` 是当前分支（分离 HEAD 或不在仓库中时为 `None`）
+    ///
+    /// `{artifact}` 要等到打包与签名之后才能确定，需要另外用 [`UrlVars::set_artifact`] 设置。
+    pub fn new(prop: &ModuleProp, config: &UpdateJsonConfig, commit: &str, branch: Option<&str>) -> Result<Self, KsmmError> {
+        let version = prop.version()?;
+        let tag = match Version::parse(version) {
+            Some(version) => version.tag(),
+            None => format!("v{}", version.trim_start_matches('v')),
+        };
+        let branch = config.branch.as_deref().or(branch).unwrap_or("main");
+
+        let mut values = BTreeMap::from([
+            ("id", prop.id()?.to_string()),
+            ("version", version.to_string()),
+            ("versionCode", prop.version_code()?.to_string()),
+            ("commit", commit.to_string()),
+            ("tag", tag),
+            ("branch", branch.to_string()),
+        ]);
+
+        let update_json_url = prop.update_json()?;
+        let repository = match (&config.repository, update_json_url) {
+            (Some(repository), _) => Some(repository.trim_end_matches('/').to_string()),
+            (None, Some(url)) => repository_from_url(url),
+            (None, None) => Some(FALLBACK_REPOSITORY.to_string()),
+        };
+        if let Some(repository) = repository {
+            if let Some((_, rest)) = repository.split_once("://")
+                && let Some((host, path)) = rest.split_once('/')
+                && let Some((owner, repo)) = path.rsplit_once('/')
+            {
+                values.insert("host", host.to_string());
+                values.insert("owner", owner.to_string());
+                values.insert("repo", repo.to_string());
+            }
+            values.insert("repository", repository);
+        }
+
+        let base_url = match (&config.base_url, update_json_url) {
+            (Some(base_url), _) => Some(base_url.trim_end_matches('/').to_string()),
+            (None, Some(url)) => url.rsplit_once('/').map(|(dir, _)| dir.to_string()),
+            (None, None) => None,
+        };
+        if let Some(base_url) = base_url {
+            values.insert("base_url", base_url);
+        }
+
+        Ok(UrlVars { values })
+    }
+
+    /// 设置发布 zip 的文件名
+    pub fn set_artifact(&mut self, artifact: &str) {
+        self.values.insert("artifact", artifact.to_string());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// 替换模板中的 `{name}` 占位符
+    pub fn expand(&self, template: &str) -> Result<String, KsmmError> {
+        config::expand_template(template, |name| self.get(name)).map_err(|e| match e {
+            TemplateError::Unclosed => KsmmError::Config(tr!(update_json_unclosed, template)),
+            TemplateError::Unknown(name) if PLACEHOLDERS.contains(&name.as_str()) => {
+                KsmmError::Config(tr!(update_json_missing_value, template, name))
+            }
+            TemplateError::Unknown(name) => {
+                KsmmError::Config(tr!(update_json_unknown_placeholder, template, name, PLACEHOLDERS.join(", ")))
+            }
+        })
+    }
+}
+
+/// 从 updateJson 地址推断仓库地址：GitLab 取 `/-/` 之前的部分（支持子组），其余平台取前两级路径
+fn repository_from_url(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let mut segments = rest.split('/');
+    let host = segments.next()?;
+    let segments: Vec<&str> = segments.collect();
+    let path = match segments.iter().position(|segment| *segment == "-") {
+        Some(index) => &segments[..index],
+        None => &segments[..segments.len().min(2)],
+    };
+    if path.len() < 2 || path.iter().any(|segment| segment.is_empty()) {
+        return None;
+    }
+
+    // raw.githubusercontent.com/{owner}/{repo}/... 中的文件属于 github.com 上的仓库
+    let host = if host == "raw.githubusercontent.com" { "github.com" } else { host };
+    Some(format!("{}://{}/{}", scheme, host, path.join("/").trim_end_matches(".git")))
+}
+
+/// update.json 的内容，字段顺序与 KernelSU 文档一致
+#[derive(Serialize)]
+struct UpdateJsonFields {
+    changelog: String,
+    version: String,
+    #[serde(rename = "versionCode")]
+    version_code: i64,
+    #[serde(rename = "zipUrl")]
+    zip_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+    #[serde(flatten)]
+    extra: BTreeMap<String, String>,
+}
+
+/// 按 `[update_json]` 生成 update.json
+///
+/// 在构建开始前创建，模板中的错误会在修改任何文件之前报告；打包与签名之后再用最终发布的 zip 生成内容。
+#[derive(Debug, Clone)]
+pub struct UpdateJson {
+    config: UpdateJsonConfig,
+    vars: UrlVars,
+    version_code: i64,
+}
+
+impl UpdateJson {
+    /// `artifact` 是将要发布的 zip 的文件名，只用于检查模板
+    pub fn new(
+        prop: &ModuleProp,
+        config: UpdateJsonConfig,
+        commit: &str,
+        branch: Option<&str>,
+        artifact: &str,
+    ) -> Result<Self, KsmmError> {
+        if let Some(field) = config.extra.keys().find(|key| RESERVED_FIELDS.contains(&key.as_str())) {
+            return Err(KsmmError::Config(tr!(update_json_reserved_field, field)));
+        }
+        let update_json = UpdateJson {
+            vars: UrlVars::new(prop, &config, commit, branch)?,
+            config,
+            version_code: prop.version_code()?,
+        };
+
+        // 用预期的文件名检查所有模板
+        let mut vars = update_json.vars.clone();
+        vars.set_artifact(artifact);
+        update_json.expand_all(&vars)?;
+        Ok(update_json)
+    }
+
+    /// 生成指向 `artifact`（最终发布的 zip，签名后为签名的版本）的 update.json
+    pub fn render(&self, artifact: &Path) -> Result<String, KsmmError> {
+        let mut vars = self.vars.clone();
+        vars.set_artifact(&artifact.file_name().unwrap_or_default().to_string_lossy());
+        let (zip_url, changelog, extra) = self.expand_all(&vars)?;
+
+        let sha256 = match self.config.sha256 {
+            true => Some(sha256_file(artifact).map_err(|e| KsmmError::io(tr!(read_file_failed, artifact.display(), e), e))?),
+            false => None,
+        };
+        let fields = UpdateJsonFields {
+            changelog,
+            version: format!("{}-{}", vars.get("tag").unwrap_or_default(), vars.get("commit").unwrap_or_default()),
+            version_code: self.version_code,
+            zip_url,
+            sha256,
+            extra,
+        };
+        serde_json::to_string_pretty(&fields).map_err(|e| KsmmError::io(tr!(build_update_json_failed, e), e.into()))
+    }
+
+    /// 展开 zipUrl、changelog 与额外字段的模板
+    fn expand_all(&self, vars: &UrlVars) -> Result<(String, String, BTreeMap<String, String>), KsmmError> {
+        let (zip_template, changelog_template) = self.config.forge.templates();
+        let zip_url = vars.expand(self.config.zip_url.as_deref().unwrap_or(zip_template))?;
+        let changelog = vars.expand(self.config.changelog.as_deref().unwrap_or(changelog_template))?;
+        let mut extra = BTreeMap::new();
+        for (key, template) in &self.config.extra {
+            extra.insert(key.clone(), vars.expand(template)?);
+        }
+        Ok((zip_url, changelog, extra))
+    }
+}
+
+/// 计算文件的 SHA-256，以小写十六进制表示
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(config: &UpdateJsonConfig) -> UrlVars {
+        let prop = ModuleProp::parse(
+            "id=demo\nversion=v1.2.0\nversionCode=12\nupdateJson=https://github.com/me/demo/releases/latest/download/update.json\n",
+        );
+        let mut vars = UrlVars::new(&prop, config, "abc1234", Some("main")).unwrap();
+        vars.set_artifact("demo-12.zip");
+        vars
+    }
+
+    #[test]
+    fn github_default_keeps_latest_download_url() {
+        let config = UpdateJsonConfig::default();
+        let (zip_url, _) = config.forge.templates();
+        assert_eq!(
+            vars(&config).expand(zip_url).unwrap(),
+            "https://github.com/me/demo/releases/latest/download/demo-12.zip"
+        );
+    }
+
+    #[test]
+    fn tag_download_url_is_opt_in() {
+        let config = UpdateJsonConfig::default();
+        assert_eq!(
+            vars(&config).expand("{repository}/releases/download/{tag}/{artifact}").unwrap(),
+            "https://github.com/me/demo/releases/download/v1.2.0/demo-12.zip"
+        );
+    }
+
+    #[test]
+    fn repository_is_inferred_from_update_json() {
+        assert_eq!(
+            repository_from_url("https://gitlab.com/group/sub/demo/-/raw/main/update.json").as_deref(),
+            Some("https://gitlab.com/group/sub/demo")
+        );
+        assert_eq!(
+            repository_from_url("https://raw.githubusercontent.com/me/demo/main/update.json").as_deref(),
+            Some("https://github.com/me/demo")
+        );
+        assert_eq!(repository_from_url("https://example.com/update.json"), None);
+    }
+}
@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+use crate::config::{self, VariantConfig};
+use crate::error::KsmmError;
+use crate::i18n::tr;
+use crate::ignore::IgnoreRules;
+use crate::module_prop::{is_valid_id, ModuleProp};
+
+/// 配置中的一个构建变体
+///
+/// 变体在正常的复制计划上追加自己的打包规则与覆盖目录，并修改打包的 module.prop，不会修改源文件。
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub name: String,
+    pub config: VariantConfig,
+}
+
+impl Variant {
+    pub fn new(name: impl Into<String>, config: VariantConfig) -> Self {
+        Variant { name: name.into(), config }
+    }
+
+    /// 修改打包的 module.prop：先覆盖字段，再追加 ID 后缀
+    pub fn apply(&self, prop: &mut ModuleProp) -> Result<(), KsmmError> {
+        for (key, value) in &self.config.module_prop {
+            prop.set(key, value.as_str());
+        }
+        if let Some(suffix) = &self.config.id_suffix {
+            let id = format!("{}{}", prop.required("id")?, suffix);
+            prop.set("id", id);
+        }
+        let id = prop.required("id")?;
+        if !is_valid_id(id) {
+            return Err(KsmmError::Config(tr!(variant_bad_id, self.name, id)));
+        }
+        Ok(())
+    }
+
+    /// `rules` 中的打包规则，位置显示为 `variants.<name>.rules:<序号>`
+    pub fn rules(&self) -> Result<IgnoreRules, KsmmError> {
+        let source = PathBuf::from(format!("variants.{}.rules", self.name));
+        IgnoreRules::parse(&source, "", &self.config.rules.join("\n"))
+    }
+
+    /// 覆盖目录的完整路径，没有配置时返回 `None`
+    pub fn overlay_dir(&self, root: &Path) -> Option<PathBuf> {
+        self.config.overlay.as_ref().map(|overlay| root.join(config::expand_home(overlay)))
+    }
+}
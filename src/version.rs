@@ -0,0 +1,232 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// 版本号中要递增的部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+    /// 递增预发布编号，正式版本则进入下一个修订号的第一个预发布版本
+    Prerelease,
+}
+
+/// module.prop 中 `version` 字段的语义化版本，允许 `v` 前缀
+///
+/// 比较时按语义化版本的优先级，忽略 `v` 前缀。
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// `-` 之后的预发布标识，例如 `beta.1`
+    pub pre: Option<String>,
+    /// 原文是否带有 `v` 前缀，输出时保持一致
+    pub prefixed: bool,
+}
+
+impl Version {
+    /// 解析 `1.2.3`、`v1.2.3` 或 `v1.2.3-beta.1`，省略的次版本号与修订号视为 0
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (prefixed, text) = match text.strip_prefix(['v', 'V']) {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        // 构建元数据不参与比较，直接丢弃
+        let text = text.split('+').next().unwrap_or(text);
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) if !pre.is_empty() => (core, Some(pre.to_string())),
+            Some(_) => return None,
+            None => (text, None),
+        };
+
+        let mut numbers = core.split('.').map(|part| part.parse::<u64>().ok());
+        let major = numbers.next()??;
+        let minor = numbers.next().unwrap_or(Some(0))?;
+        let patch = numbers.next().unwrap_or(Some(0))?;
+        if numbers.next().is_some() {
+            return None;
+        }
+
+        Some(Version { major, minor, patch, pre, prefixed })
+    }
+
+    /// 递增版本号，预发布版本按 npm 的规则先发布为对应的正式版本：
+    /// `1.3.0-rc.1` 递增次版本号得到 `1.3.0`，`1.2.3` 递增次版本号得到 `1.3.0`
+    pub fn bump(&self, bump: Bump, pre_id: &str) -> Version {
+        let mut next = Version { pre: None, ..self.clone() };
+        match bump {
+            Bump::Major => {
+                if self.pre.is_none() || self.minor != 0 || self.patch != 0 {
+                    next.major += 1;
+                    next.minor = 0;
+                    next.patch = 0;
+                }
+            }
+            Bump::Minor => {
+                if self.pre.is_none() || self.patch != 0 {
+                    next.minor += 1;
+                    next.patch = 0;
+                }
+            }
+            Bump::Patch => {
+                if self.pre.is_none() {
+                    next.patch += 1;
+                }
+            }
+            Bump::Prerelease => match &self.pre {
+                Some(pre) => next.pre = Some(increment_pre(pre)),
+                None => {
+                    next.patch += 1;
+                    next.pre = Some(format!("{}.1", pre_id));
+                }
+            },
+        }
+        next
+    }
+
+    /// 对应的 git 标签与更新日志标题，总是带有 `v` 前缀，与发布工作流匹配的 `v*` 一致
+    pub fn tag(&self) -> String {
+        Version { prefixed: true, ..self.clone() }.to_string()
+    }
+
+    /// `major * 10000 + minor * 100 + patch`，次版本号或修订号超过 99 时无法表示
+    ///
+    /// 预发布版本与正式版本得到相同的值。
+    pub fn version_code(&self) -> Option<i64> {
+        if self.minor > 99 || self.patch > 99 {
+            return None;
+        }
+        let code = self.major.checked_mul(10000)? + self.minor * 100 + self.patch;
+        i64::try_from(code).ok()
+    }
+}
+
+/// 递增预发布标识中最后一个数字，没有数字时追加 `.1`
+fn increment_pre(pre: &str) -> String {
+    let mut parts: Vec<String> = pre.split('.').map(str::to_string).collect();
+    match parts.last().and_then(|last| last.parse::<u64>().ok()) {
+        Some(number) => {
+            let last = parts.len() - 1;
+            parts[last] = (number + 1).to_string();
+        }
+        None => parts.push("1".to_string()),
+    }
+    parts.join(".")
+}
+
+/// 比较预发布标识：数字按数值比较且低于字母标识，前缀相同时较短的较低
+fn compare_pre(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+    loop {
+        let ordering = match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_pre(a, b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.prefixed {
+            f.write_str("v")?;
+        }
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(text: &str) -> Version {
+        Version::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parse_accepts_prefix_and_short_forms() {
+        assert_eq!(v("v1.2.3").to_string(), "v1.2.3");
+        assert_eq!(v("1.2").to_string(), "1.2.0");
+        assert_eq!(v("1.2.3+build.5").to_string(), "1.2.3");
+        assert_eq!(v("1.2.3-beta.1").pre.as_deref(), Some("beta.1"));
+        assert!(Version::parse("1.2.3.4").is_none());
+        assert!(Version::parse("1.x").is_none());
+        assert!(Version::parse("1.2.3-").is_none());
+    }
+
+    #[test]
+    fn bump_follows_npm_rules() {
+        assert_eq!(v("1.2.3").bump(Bump::Major, "beta").to_string(), "2.0.0");
+        assert_eq!(v("1.2.3").bump(Bump::Minor, "beta").to_string(), "1.3.0");
+        assert_eq!(v("v1.2.3").bump(Bump::Patch, "beta").to_string(), "v1.2.4");
+        assert_eq!(v("1.2.3").bump(Bump::Prerelease, "beta").to_string(), "1.2.4-beta.1");
+        assert_eq!(v("1.2.4-beta.1").bump(Bump::Prerelease, "beta").to_string(), "1.2.4-beta.2");
+        assert_eq!(v("1.2.4-rc").bump(Bump::Prerelease, "beta").to_string(), "1.2.4-rc.1");
+        // 预发布版本先发布为对应的正式版本
+        assert_eq!(v("1.3.0-rc.1").bump(Bump::Minor, "beta").to_string(), "1.3.0");
+        assert_eq!(v("2.0.0-rc.1").bump(Bump::Major, "beta").to_string(), "2.0.0");
+        assert_eq!(v("1.2.4-rc.1").bump(Bump::Patch, "beta").to_string(), "1.2.4");
+        assert_eq!(v("1.2.4-rc.1").bump(Bump::Minor, "beta").to_string(), "1.3.0");
+    }
+
+    #[test]
+    fn compare_uses_semver_precedence() {
+        assert!(v("1.0.0-alpha") < v("1.0.0-alpha.1"));
+        assert!(v("1.0.0-alpha.1") < v("1.0.0-alpha.beta"));
+        assert!(v("1.0.0-beta.2") < v("1.0.0-beta.11"));
+        assert!(v("1.0.0-rc.1") < v("1.0.0"));
+        assert!(v("1.0.0") < v("1.0.1"));
+        assert!(v("1.9.0") < v("1.10.0"));
+        assert_eq!(v("v1.2.3"), v("1.2.3"));
+    }
+
+    #[test]
+    fn tag_and_version_code() {
+        assert_eq!(v("1.2.3-beta.1").tag(), "v1.2.3-beta.1");
+        assert_eq!(v("1.2.3").version_code(), Some(10203));
+        assert_eq!(v("1.100.0").version_code(), None);
+    }
+}